@@ -10,11 +10,11 @@ use std::{
 //Convert byte size to string
 fn byte_size_to_string(size: usize) -> String {
     if size < 1024 {
-        return format!("{}B", size);
+        format!("{}B", size)
     } else if size < 1024 * 1024 {
-        return format!("{}KB", (size / 1024));
+        format!("{}KB", (size / 1024))
     } else {
-        return format!("{}MB", (size / 1024 / 1024));
+        format!("{}MB", (size / 1024 / 1024))
     }
 }
 
@@ -25,7 +25,7 @@ fn main() {
     )
     .unwrap();
     request.set_header("Connection", "close");
-    request.content_type = ContentTypes::OctetStream;
+    request.set_accept(ContentTypes::OctetStream);
     match request.send() {
         Ok(mut e) => {
             let mut file = File::create("./20MB.zip").unwrap();
@@ -37,10 +37,7 @@ fn main() {
             let stdout = io::stdout();
 
             let content_len = match e.headers.iter_mut().find(|h| h.name == "Content-Length") {
-                Some(header) => match header.value.parse::<usize>() {
-                    Ok(d) => d,
-                    Err(_) => 0,
-                },
+                Some(header) => header.value.parse::<usize>().unwrap_or_default(),
                 None => 0,
             };
 
@@ -61,16 +58,7 @@ fn main() {
                             since_ms = Instant::now();
                         }
                         collected_byte_len += buffer.len();
-                        if content_len == 0 {
-                            println!(
-                                "Downloading Without Content Len: {}bytes with: {}kbps | Active Time: {}s",
-                                byte_size_to_string(content_len),
-                                byte_size_to_string(speed_kbps) ,
-                                elapsed_secs,
-                            );
-                        } else {
-                            let percent = stream_read_len / content_len * 100;
-
+                        if let Some(percent) = (stream_read_len * 100).checked_div(content_len) {
                             let output = format!(
                                 "Downloading: {} of {}; {}% {}ps | Active Time: {}s\n",
                                 byte_size_to_string(stream_read_len),
@@ -80,8 +68,15 @@ fn main() {
                                 elapsed_secs,
                             );
                             stdout.lock().write_all(output.as_bytes()).unwrap()
+                        } else {
+                            println!(
+                                "Downloading Without Content Len: {}bytes with: {}kbps | Active Time: {}s",
+                                byte_size_to_string(content_len),
+                                byte_size_to_string(speed_kbps) ,
+                                elapsed_secs,
+                            );
                         }
-                        file.write(&buffer).unwrap();
+                        file.write_all(&buffer).unwrap();
                     }
                     Err(e) => {
                         panic!("E {}", e)
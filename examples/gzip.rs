@@ -1,23 +1,21 @@
-use libflate::gzip::Decoder;
 use menemen::request::{Request, RequestTypes};
-use std::io::Read;
 
 fn main() {
+    // Accept-Encoding is set by default and the body is decoded while reading
     let mut request = Request::new("http://behemehal.org", RequestTypes::GET).unwrap();
-    request.set_header(&"Accept-Encoding", &"gzip");
 
     let mut response = request.send().unwrap();
 
     println!("Response info: {:?}", response.response_info);
     println!("Response headers: {:?}", response.headers);
 
-    // Pipe response stream through gzip decoder
-    let mut decoder = Decoder::new(&mut response.stream).unwrap();
-
-    let mut text_buffer = Vec::new();
-
     // Read decoded response into text buffer
-    decoder.read_to_end(&mut text_buffer).unwrap();
+    let text_buffer = response.bytes().unwrap();
 
     println!("Text: {}", String::from_utf8_lossy(&text_buffer));
+    println!(
+        "Encodings: {:?}, {} bytes received",
+        response.stream.content_encodings(),
+        response.stream.encoded_bytes_read()
+    );
 }
@@ -0,0 +1,15 @@
+use menemen::multipart::Multipart;
+use menemen::request::{Request, RequestTypes};
+
+fn main() {
+    let mut request = Request::new("https://postman-echo.com/post", RequestTypes::POST).unwrap();
+
+    // Parts are read while the request is sent
+    let mut form = Multipart::new();
+    form.add_text("name", "Menemen");
+    form.add_file("recipe", "./examples/post.json").unwrap();
+
+    let mut response = request.send_multipart(form).unwrap();
+    println!("Text: {}", response.text().unwrap());
+    println!("Response info: {:?}", response.response_info);
+}
@@ -0,0 +1,267 @@
+use crate::{encoding, lock::lock, request::Header};
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, Mutex};
+
+/// Hash algorithm of the digest challenge
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DigestAlgorithm {
+    Md5,
+    Sha256,
+}
+
+impl DigestAlgorithm {
+    /// Hashes the value to lowercase hex
+    fn hash(&self, value: &str) -> String {
+        let digest = match self {
+            DigestAlgorithm::Md5 => Md5::digest(value.as_bytes()).to_vec(),
+            DigestAlgorithm::Sha256 => Sha256::digest(value.as_bytes()).to_vec(),
+        };
+        digest.iter().map(|x| format!("{:02x}", x)).collect()
+    }
+}
+
+/// Digest challenge of the server, reused for the following requests until the server sends a new nonce
+#[derive(Clone, Debug)]
+struct DigestChallenge {
+    realm: String,
+    nonce: String,
+    opaque: Option<String>,
+    algorithm: DigestAlgorithm,
+    /// Algorithm name as the server sent it, `-sess` variants hash the client nonce into the credentials
+    algorithm_name: String,
+    session: bool,
+    /// Server supports `qop=auth`, else the legacy RFC 2069 response is computed
+    qop_auth: bool,
+    /// Number of requests sent with the nonce
+    nonce_count: u32,
+    /// Credentials hash and client nonce of `-sess` variants, computed by the first request of the nonce
+    session_credentials: Option<(String, String)>,
+}
+
+/// Credentials of RFC 7616 Digest authentication, computed from the `WWW-Authenticate: Digest` challenge of a `401` response.
+/// Clones share the challenge, so requests of a [`crate::client::Client`] reuse the nonce by counting it
+/// #### https://www.rfc-editor.org/rfc/rfc7616
+/// ## Example
+/// ```no_run
+/// use menemen::auth::DigestAuth;
+/// use menemen::request::{Request, RequestTypes};
+///
+/// let mut request = Request::new("http://behemehal.org/private", RequestTypes::GET).unwrap();
+/// request.set_digest_auth(DigestAuth::new("user", "passwd"));
+/// let response = request.send().unwrap();
+/// ```
+#[derive(Clone)]
+pub struct DigestAuth {
+    username: String,
+    password: String,
+    challenge: Arc<Mutex<Option<DigestChallenge>>>,
+}
+
+impl std::fmt::Debug for DigestAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DigestAuth")
+            .field("username", &self.username)
+            .finish()
+    }
+}
+
+impl DigestAuth {
+    /// Create a new [`DigestAuth`]
+    /// ## Parameters
+    /// * `username` - The user name
+    /// * `password` - The password
+    pub fn new(username: &str, password: &str) -> DigestAuth {
+        DigestAuth {
+            username: username.to_string(),
+            password: password.to_string(),
+            challenge: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Get the user name
+    pub fn username(&self) -> String {
+        self.username.clone()
+    }
+
+    /// Stores the supported digest challenge of the `WWW-Authenticate` headers, SHA-256 is preferred over MD5
+    /// ## Parameters
+    /// * `headers` - Headers of the `401` response
+    /// ## Returns
+    /// Whether a supported challenge is found, so the request can be retried
+    pub(crate) fn challenge(&self, headers: &[Header]) -> bool {
+        let mut challenges = headers
+            .iter()
+            .filter(|x| x.name.eq_ignore_ascii_case("WWW-Authenticate"))
+            .flat_map(|x| parse_challenges(&x.value))
+            .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("Digest"))
+            .filter_map(|(_, params)| digest_challenge(&params))
+            .collect::<Vec<_>>();
+        challenges.sort_by_key(|x| x.algorithm == DigestAlgorithm::Md5);
+        match challenges.into_iter().next() {
+            Some(challenge) => {
+                *lock(&self.challenge) = Some(challenge);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Builds the `Authorization` header value from the stored challenge, counting the nonce
+    /// ## Parameters
+    /// * `method` - Method of the request
+    /// * `uri` - Target of the request
+    /// ## Returns
+    /// Header value if a challenge is received before else [`None`]
+    pub(crate) fn authorization(&self, method: &str, uri: &str) -> Option<String> {
+        let mut challenge = lock(&self.challenge);
+        let challenge = challenge.as_mut()?;
+        challenge.nonce_count += 1;
+        let nonce_count = format!("{:08x}", challenge.nonce_count);
+        let algorithm = challenge.algorithm;
+        let hash = |x: &str| algorithm.hash(x);
+
+        let (credentials, cnonce) = match &challenge.session_credentials {
+            Some(session_credentials) => session_credentials.clone(),
+            None => {
+                let cnonce = encoding::random_hex();
+                let mut credentials = hash(&format!(
+                    "{}:{}:{}",
+                    self.username, challenge.realm, self.password
+                ));
+                //RFC 7616 section 3.4.2, the session hash is kept while the nonce is reused
+                if challenge.session {
+                    credentials = hash(&format!("{}:{}:{}", credentials, challenge.nonce, cnonce));
+                    challenge.session_credentials = Some((credentials.clone(), cnonce.clone()));
+                }
+                (credentials, cnonce)
+            }
+        };
+        let request = hash(&format!("{}:{}", method, uri));
+        let response = if challenge.qop_auth {
+            hash(&format!(
+                "{}:{}:{}:{}:auth:{}",
+                credentials, challenge.nonce, nonce_count, cnonce, request
+            ))
+        } else {
+            hash(&format!("{}:{}:{}", credentials, challenge.nonce, request))
+        };
+
+        let mut value = format!(
+            "Digest username=\"{}\", realm=\"{}\", nonce=\"{}\", uri=\"{}\", algorithm={}, response=\"{}\"",
+            quote(&self.username),
+            quote(&challenge.realm),
+            quote(&challenge.nonce),
+            quote(uri),
+            challenge.algorithm_name,
+            response
+        );
+        if challenge.qop_auth {
+            value += &format!(", qop=auth, nc={}, cnonce=\"{}\"", nonce_count, cnonce);
+        }
+        if let Some(opaque) = &challenge.opaque {
+            value += &format!(", opaque=\"{}\"", quote(opaque));
+        }
+        Some(value)
+    }
+}
+
+/// Escapes the value of a quoted string
+fn quote(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Builds the challenge from the parameters, challenges with unsupported algorithm or qop are skipped
+fn digest_challenge(params: &[(String, String)]) -> Option<DigestChallenge> {
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+    };
+    let algorithm_name = param("algorithm").unwrap_or_else(|| "MD5".to_string());
+    let (algorithm, session) = match algorithm_name.to_uppercase().as_str() {
+        "MD5" => (DigestAlgorithm::Md5, false),
+        "MD5-SESS" => (DigestAlgorithm::Md5, true),
+        "SHA-256" => (DigestAlgorithm::Sha256, false),
+        "SHA-256-SESS" => (DigestAlgorithm::Sha256, true),
+        _ => return None,
+    };
+    let qop = param("qop");
+    //Only auth-int needs the hash of the body, which is not supported
+    if qop
+        .as_ref()
+        .is_some_and(|x| !x.split(',').any(|x| x.trim().eq_ignore_ascii_case("auth")))
+    {
+        return None;
+    }
+    Some(DigestChallenge {
+        realm: param("realm")?,
+        nonce: param("nonce")?,
+        opaque: param("opaque"),
+        algorithm,
+        algorithm_name,
+        session,
+        qop_auth: qop.is_some(),
+        nonce_count: 0,
+        session_credentials: None,
+    })
+}
+
+/// Challenge scheme and its lowercase named parameters
+type Challenge = (String, Vec<(String, String)>);
+
+/// Parses the challenges of `WWW-Authenticate` header, a header may carry many of them
+fn parse_challenges(value: &str) -> Vec<Challenge> {
+    //Split on commas outside of quoted strings
+    let mut items = vec![String::new()];
+    let mut quoted = false;
+    let mut escaped = false;
+    for x in value.chars() {
+        match x {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ',' if !quoted => {
+                items.push(String::new());
+                continue;
+            }
+            _ => {}
+        }
+        items.last_mut().unwrap().push(x);
+    }
+
+    let mut challenges: Vec<Challenge> = Vec::new();
+    for item in items {
+        let mut item = item.trim();
+        let scheme = item.split(' ').next().unwrap_or_default();
+        if !scheme.is_empty() && !scheme.contains('=') {
+            challenges.push((scheme.to_string(), Vec::new()));
+            item = item[scheme.len()..].trim();
+        }
+        let (Some((_, params)), Some((key, value))) = (challenges.last_mut(), item.split_once('='))
+        else {
+            continue;
+        };
+        params.push((key.trim().to_lowercase(), unquote(value.trim())));
+    }
+    challenges
+}
+
+/// Removes the quotes and escapes of a quoted string, tokens are returned as they are
+fn unquote(value: &str) -> String {
+    let Some(value) = value.strip_prefix('"').and_then(|x| x.strip_suffix('"')) else {
+        return value.to_string();
+    };
+    let mut output = String::with_capacity(value.len());
+    let mut escaped = false;
+    for x in value.chars() {
+        if x == '\\' && !escaped {
+            escaped = true;
+        } else {
+            output.push(x);
+            escaped = false;
+        }
+    }
+    output
+}
@@ -0,0 +1,513 @@
+use crate::request::BodyCompression;
+use crate::{
+    error::LimitKind,
+    http::StatusCode,
+    lock::lock,
+    request::Header,
+    response::{ResponseInfo, ResponseLimits},
+    transport::Transport,
+};
+#[cfg(feature = "gzip")]
+use flate2::read::GzDecoder;
+#[cfg(feature = "deflate")]
+use flate2::read::{DeflateDecoder, ZlibDecoder};
+use std::{
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    sync::{Arc, Mutex},
+};
+
+/// State of the chunked body reader
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ChunkState {
+    /// Next line is the size of the chunk
+    Size,
+    /// This many bytes left in the current chunk
+    Data(u64),
+    /// Chunk data is followed by a line break
+    DataEnd,
+    /// Last chunk and the trailer section are read
+    Done,
+}
+
+/// How the end of the body is found
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Framing {
+    /// Body has this many bytes left
+    Length(u64),
+    /// Body is sent in chunks
+    Chunked(ChunkState),
+    /// Body continues until the connection is closed
+    Close,
+}
+
+/// Reads the body as it is sent by the server with the transfer framing removed
+struct FramedReader {
+    stream: Transport,
+    framing: Framing,
+    encoded_bytes: u64,
+    /// Maximum bytes of a chunk size line and of the trailer section
+    max_line_bytes: usize,
+    /// Trailer fields, set once the end of the body is reached
+    trailers: Option<Vec<Header>>,
+}
+
+/// Error of an exceeded response limit, converted to [`crate::error::RequestErrors::LimitExceeded`]
+fn limit_exceeded(limit: LimitKind) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, limit)
+}
+
+/// Error of a body that ended before its framing says
+fn unexpected_eof() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "Connection closed before body received",
+    )
+}
+
+impl FramedReader {
+    /// Reads a `\r\n` terminated line of the chunked framing
+    fn read_line(&mut self, max_length: usize) -> io::Result<String> {
+        let mut line = Vec::new();
+        let limit = max_length as u64 + 2;
+        let read = (&mut self.stream)
+            .take(limit)
+            .read_until(b'\n', &mut line)?;
+        if !line.ends_with(b"\n") {
+            return Err(if read as u64 == limit {
+                limit_exceeded(LimitKind::HeaderBytes)
+            } else {
+                unexpected_eof()
+            });
+        }
+        line.pop();
+        if line.ends_with(b"\r") {
+            line.pop();
+        }
+        Ok(String::from_utf8_lossy(&line).to_string())
+    }
+
+    /// Reads the trailer section following the last chunk
+    fn read_trailers(&mut self) -> io::Result<Vec<Header>> {
+        let mut trailers = Vec::new();
+        let mut trailer_bytes = 0;
+        loop {
+            let line = self.read_line(self.max_line_bytes.saturating_sub(trailer_bytes))?;
+            if line.is_empty() {
+                return Ok(trailers);
+            }
+            trailer_bytes += line.len();
+            let trailer = Header::parse(&line).map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidData, "Malformed trailer field")
+            })?;
+            trailers.push(trailer);
+        }
+    }
+
+    /// Reads the chunked body, moving between sizes and data of the chunks
+    fn read_chunked(&mut self, state: ChunkState, buf: &mut [u8]) -> io::Result<usize> {
+        let mut state = state;
+        loop {
+            match state {
+                ChunkState::Done => {
+                    self.framing = Framing::Chunked(ChunkState::Done);
+                    return Ok(0);
+                }
+                ChunkState::Size => {
+                    let line = self.read_line(self.max_line_bytes)?;
+                    let size = line.split(';').next().unwrap_or_default().trim();
+                    let size = u64::from_str_radix(size, 16).map_err(|_| {
+                        io::Error::new(io::ErrorKind::InvalidData, "Malformed chunk size")
+                    })?;
+                    state = if size == 0 {
+                        self.trailers = Some(self.read_trailers()?);
+                        ChunkState::Done
+                    } else {
+                        ChunkState::Data(size)
+                    };
+                }
+                ChunkState::Data(remaining) => {
+                    let max = buf.len().min(remaining as usize);
+                    let read = self.stream.read(&mut buf[..max])?;
+                    if read == 0 {
+                        return Err(unexpected_eof());
+                    }
+                    self.framing = Framing::Chunked(if read as u64 == remaining {
+                        ChunkState::DataEnd
+                    } else {
+                        ChunkState::Data(remaining - read as u64)
+                    });
+                    return Ok(read);
+                }
+                ChunkState::DataEnd => {
+                    if !self.read_line(0)?.is_empty() {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "Chunk data is longer than its size",
+                        ));
+                    }
+                    state = ChunkState::Size;
+                }
+            }
+        }
+    }
+}
+
+impl Read for FramedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let read = match self.framing {
+            Framing::Length(0) => {
+                self.trailers.get_or_insert_with(Vec::new);
+                0
+            }
+            Framing::Length(remaining) => {
+                let max = buf.len().min(remaining as usize);
+                let read = self.stream.read(&mut buf[..max])?;
+                if read == 0 {
+                    return Err(unexpected_eof());
+                }
+                self.framing = Framing::Length(remaining - read as u64);
+                read
+            }
+            Framing::Chunked(state) => self.read_chunked(state, buf)?,
+            Framing::Close => {
+                let read = self.stream.read(buf)?;
+                if read == 0 {
+                    self.trailers.get_or_insert_with(Vec::new);
+                }
+                read
+            }
+        };
+        self.encoded_bytes += read as u64;
+        Ok(read)
+    }
+}
+
+/// Reads the framed body shared with [`Body`], so it can reach the trailers behind the decoders
+struct SharedReader(Arc<Mutex<FramedReader>>);
+
+impl Read for SharedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        lock(&self.0).read(buf)
+    }
+}
+
+/// Fails reading when the decoded body grows past the limit
+struct LimitedReader {
+    inner: Box<dyn Read + Send + Sync>,
+    remaining: u64,
+}
+
+impl Read for LimitedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if read as u64 > self.remaining {
+            return Err(limit_exceeded(LimitKind::BodySize));
+        }
+        self.remaining -= read as u64;
+        Ok(read)
+    }
+}
+
+/// Finds the value of the header, names are compared case insensitively
+pub(crate) fn find_header<'a>(headers: &'a [Header], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|x| x.name.eq_ignore_ascii_case(name))
+        .map(|x| x.value.trim())
+}
+
+/// Splits comma separated header values into lowercase tokens
+fn header_tokens(headers: &[Header], name: &str) -> Vec<String> {
+    headers
+        .iter()
+        .filter(|x| x.name.eq_ignore_ascii_case(name))
+        .flat_map(|x| x.value.split(','))
+        .map(|x| x.trim().to_lowercase())
+        .filter(|x| !x.is_empty())
+        .collect()
+}
+
+/// Value of the `Accept-Encoding` header listing the encodings enabled with features
+/// ## Returns
+/// Encodings like `gzip, deflate, br` else [`None`] if no decoding feature is enabled
+pub fn accept_encoding() -> Option<String> {
+    let encodings = [
+        (cfg!(feature = "gzip"), "gzip"),
+        (cfg!(feature = "deflate"), "deflate"),
+        (cfg!(feature = "brotli"), "br"),
+    ]
+    .iter()
+    .filter(|(enabled, _)| *enabled)
+    .map(|(_, encoding)| *encoding)
+    .collect::<Vec<_>>();
+    if encodings.is_empty() {
+        None
+    } else {
+        Some(encodings.join(", "))
+    }
+}
+
+/// Checks if the content encoding can be decoded with enabled features
+fn is_supported(encoding: &str) -> bool {
+    match encoding {
+        "gzip" | "x-gzip" => cfg!(feature = "gzip"),
+        "deflate" => cfg!(feature = "deflate"),
+        "br" => cfg!(feature = "brotli"),
+        _ => encoding == "identity",
+    }
+}
+
+/// Wraps the reader with the decoder of the encoding, encoding should be supported
+fn decoder(encoding: &str, reader: Box<dyn Read + Send + Sync>) -> Box<dyn Read + Send + Sync> {
+    match encoding {
+        #[cfg(feature = "gzip")]
+        "gzip" | "x-gzip" => Box::new(GzDecoder::new(reader)),
+        #[cfg(feature = "deflate")]
+        "deflate" => {
+            //Servers send deflate both zlib wrapped and raw, zlib header tells them apart
+            let mut reader = BufReader::new(reader);
+            let zlib = match reader.fill_buf() {
+                Ok([cmf, flg, ..]) => {
+                    cmf & 0x0f == 8 && u16::from_be_bytes([*cmf, *flg]).is_multiple_of(31)
+                }
+                _ => true,
+            };
+            if zlib {
+                Box::new(ZlibDecoder::new(reader))
+            } else {
+                Box::new(DeflateDecoder::new(reader))
+            }
+        }
+        #[cfg(feature = "brotli")]
+        "br" => Box::new(brotli::Decompressor::new(reader, 4096)),
+        _ => reader,
+    }
+}
+
+/// Response body, removes the transfer framing and decodes the content encodings.
+/// Implements [`std::io::Read`] and [`std::io::BufRead`]
+pub struct Body {
+    reader: BufReader<Box<dyn Read + Send + Sync>>,
+    framed: Arc<Mutex<FramedReader>>,
+    content_encodings: Vec<String>,
+}
+
+impl std::fmt::Debug for Body {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Body")
+            .field("encoded_bytes", &self.encoded_bytes_read())
+            .field("content_encodings", &self.content_encodings)
+            .field("trailers", &self.trailers())
+            .finish()
+    }
+}
+
+impl Body {
+    /// Builds the body reader of the response
+    /// ## Parameters
+    /// * `stream` - The connection positioned at the start of the body
+    /// * `response_info` - Status of the response, some statuses never have a body
+    /// * `headers` - Headers of the response
+    /// * `decompress` - Decode the content encodings enabled with features
+    /// * `limits` - Limits of the trailers and the decoded body size [`ResponseLimits`]
+    pub(crate) fn new(
+        stream: Transport,
+        response_info: &ResponseInfo,
+        headers: &[Header],
+        decompress: bool,
+        limits: &ResponseLimits,
+    ) -> Body {
+        let transfer_encodings = header_tokens(headers, "Transfer-Encoding");
+        let status = response_info.status_code;
+        let framing = if status.is_informational()
+            || status == StatusCode::NO_CONTENT
+            || status == StatusCode::NOT_MODIFIED
+        {
+            Framing::Length(0)
+        } else if transfer_encodings.last().map(|x| x.as_str()) == Some("chunked") {
+            Framing::Chunked(ChunkState::Size)
+        } else if !transfer_encodings.is_empty() {
+            Framing::Close
+        } else {
+            match find_header(headers, "Content-Length").map(|x| x.parse::<u64>()) {
+                Some(Ok(length)) => Framing::Length(length),
+                _ => Framing::Close,
+            }
+        };
+        let framed = Arc::new(Mutex::new(FramedReader {
+            stream,
+            framing,
+            encoded_bytes: 0,
+            max_line_bytes: limits.max_header_bytes,
+            trailers: None,
+        }));
+        let mut reader: Box<dyn Read + Send + Sync> = Box::new(SharedReader(framed.clone()));
+
+        let content_encodings = header_tokens(headers, "Content-Encoding");
+        //Unknown encoding leaves the body as it is, since outer ones can't be reached
+        if decompress && content_encodings.iter().all(|x| is_supported(x)) {
+            //Encodings are listed in the order they are applied
+            for encoding in content_encodings.iter().rev() {
+                reader = decoder(encoding, reader);
+            }
+        }
+        if let Some(max_body_size) = limits.max_body_size {
+            reader = Box::new(LimitedReader {
+                inner: reader,
+                remaining: max_body_size,
+            });
+        }
+        Body {
+            reader: BufReader::new(reader),
+            framed,
+            content_encodings,
+        }
+    }
+
+    /// Get the number of body bytes read from the connection before decoding, without the chunked framing
+    pub fn encoded_bytes_read(&self) -> u64 {
+        lock(&self.framed).encoded_bytes
+    }
+
+    /// Get the trailer fields sent after a chunked body
+    /// ## Returns
+    /// Trailer [`Header`]s once the body is read to the end, empty when the body has no trailers, else [`None`]
+    /// ## Example
+    /// ```no_run
+    /// use menemen::request::{Request, RequestTypes};
+    /// use std::io::Read;
+    ///
+    /// let mut request = Request::new("http://behemehal.org/stream", RequestTypes::GET).unwrap();
+    /// let mut body = request.send().unwrap().into_reader();
+    /// assert!(body.trailers().is_none());
+    /// body.read_to_end(&mut Vec::new()).unwrap();
+    /// for trailer in body.trailers().unwrap() {
+    ///     println!("{}: {}", trailer.name, trailer.value);
+    /// }
+    /// ```
+    pub fn trailers(&self) -> Option<Vec<Header>> {
+        lock(&self.framed).trailers.clone()
+    }
+
+    /// Reads the rest of the framing once the decoded body ends,
+    /// decoders may stop before the last chunk and the trailers are read
+    fn finish(&mut self) -> io::Result<()> {
+        let mut framed = lock(&self.framed);
+        if framed.trailers.is_none() {
+            io::copy(&mut *framed, &mut io::sink())?;
+        }
+        Ok(())
+    }
+
+    /// Get the content encodings of the body listed in `Content-Encoding` header
+    pub fn content_encodings(&self) -> Vec<String> {
+        self.content_encodings.clone()
+    }
+}
+
+impl Read for Body {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.reader.read(buf)?;
+        if read == 0 && !buf.is_empty() {
+            self.finish()?;
+        }
+        Ok(read)
+    }
+}
+
+impl BufRead for Body {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.reader.fill_buf()?.is_empty() {
+            self.finish()?;
+        }
+        self.reader.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.reader.consume(amt)
+    }
+}
+
+/// Writes the request body in chunked framing, every write becomes a chunk
+pub(crate) struct ChunkedWriter<W: Write> {
+    stream: BufWriter<W>,
+}
+
+impl<W: Write> ChunkedWriter<W> {
+    /// Create a new [`ChunkedWriter`] writing to the stream
+    pub(crate) fn new(stream: W) -> ChunkedWriter<W> {
+        ChunkedWriter {
+            stream: BufWriter::new(stream),
+        }
+    }
+
+    /// Writes the last chunk and the trailer fields ending the body
+    /// ## Parameters
+    /// * `trailers` - The trailer [`Header`]s sent after the last chunk
+    pub(crate) fn finish(mut self, trailers: &[Header]) -> io::Result<()> {
+        self.stream.write_all(b"0\r\n")?;
+        for trailer in trailers {
+            write!(self.stream, "{}:{}\r\n", trailer.name, trailer.value)?;
+        }
+        self.stream.write_all(b"\r\n")?;
+        self.stream.flush()
+    }
+}
+
+impl<W: Write> Write for ChunkedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        //Empty chunk would end the body
+        if !buf.is_empty() {
+            write!(self.stream, "{:x}\r\n", buf.len())?;
+            self.stream.write_all(buf)?;
+            self.stream.write_all(b"\r\n")?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+/// Compresses the body while copying it to the output
+/// ## Parameters
+/// * `compression` - The [`BodyCompression`] to apply
+/// * `body` - The uncompressed body
+/// * `output` - The stream compressed body is written to
+#[cfg_attr(
+    not(any(feature = "gzip", feature = "deflate", feature = "zstd")),
+    allow(unused, unreachable_code)
+)]
+pub(crate) fn compress(
+    compression: BodyCompression,
+    body: &mut dyn Read,
+    output: &mut dyn Write,
+) -> io::Result<()> {
+    //Buffered so encoders don't produce a chunk for every small write
+    let mut output = BufWriter::with_capacity(16 * 1024, output);
+    match compression {
+        #[cfg(feature = "gzip")]
+        BodyCompression::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(&mut output, Default::default());
+            io::copy(body, &mut encoder)?;
+            encoder.finish()?;
+        }
+        #[cfg(feature = "deflate")]
+        BodyCompression::Deflate => {
+            let mut encoder = flate2::write::ZlibEncoder::new(&mut output, Default::default());
+            io::copy(body, &mut encoder)?;
+            encoder.finish()?;
+        }
+        #[cfg(feature = "zstd")]
+        BodyCompression::Zstd => {
+            let mut encoder = zstd::stream::write::Encoder::new(&mut output, 0)?;
+            io::copy(body, &mut encoder)?;
+            encoder.finish()?;
+        }
+    }
+    output.flush()
+}
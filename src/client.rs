@@ -0,0 +1,224 @@
+use crate::{
+    auth::DigestAuth,
+    connector::Connector,
+    cookie::CookieJar,
+    dns::{Resolve, SystemResolver},
+    proxy::Proxy,
+    request::{Request, RequestTypes},
+    response::ResponseLimits,
+    transport::{AddressFamily, SocketOptions, Timeouts},
+};
+use std::sync::Arc;
+
+/// Client keeps the configuration shared by the requests it creates
+#[derive(Clone, Debug)]
+pub struct Client {
+    /// Proxy the requests are routed through [`Proxy`]
+    proxy: Option<Proxy>,
+    /// Use proxies from environment variables
+    env_proxy: bool,
+    /// Timeouts of the requests [`Timeouts`]
+    timeouts: Timeouts,
+    /// Resolver of the host names shared by the requests [`Resolve`]
+    resolver: Arc<dyn Resolve>,
+    /// Address families to connect [`AddressFamily`]
+    address_family: AddressFamily,
+    /// Options of the sockets [`SocketOptions`]
+    socket_options: SocketOptions,
+    /// Connector replacing the default one [`Connector`]
+    connector: Option<Arc<dyn Connector>>,
+    /// Decode the content encodings of the response bodies
+    decompress: bool,
+    /// Limits applied while reading the responses [`ResponseLimits`]
+    limits: ResponseLimits,
+    /// Jar shared by the requests to keep the cookies [`CookieJar`]
+    cookie_jar: Option<Arc<CookieJar>>,
+    /// Digest credentials shared by the requests, so they reuse the challenge [`DigestAuth`]
+    digest_auth: Option<DigestAuth>,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Client {
+            proxy: None,
+            env_proxy: true,
+            timeouts: Timeouts::default(),
+            resolver: Arc::new(SystemResolver),
+            address_family: AddressFamily::default(),
+            socket_options: SocketOptions::default(),
+            connector: None,
+            decompress: true,
+            limits: ResponseLimits::default(),
+            cookie_jar: None,
+            digest_auth: None,
+        }
+    }
+}
+
+impl Client {
+    /// Create a new [`Client`] with default configuration
+    pub fn new() -> Client {
+        Client::default()
+    }
+
+    /// Set proxy for the requests created by this client
+    /// ## Parameters
+    /// * `proxy` - The [`Proxy`] the requests will be routed through
+    pub fn set_proxy(&mut self, proxy: Proxy) {
+        self.proxy = Some(proxy);
+    }
+
+    /// Enable or disable proxies from `http_proxy`, `HTTPS_PROXY`, `ALL_PROXY` and `NO_PROXY` variables
+    /// ## Parameters
+    /// * `enabled` - Whether environment variables should be used, enabled by default
+    pub fn set_env_proxy(&mut self, enabled: bool) {
+        self.env_proxy = enabled;
+    }
+
+    /// Get proxy of the client
+    /// ## Returns
+    /// [`Proxy`] if the client routes requests through a proxy else [`None`]
+    pub fn get_proxy(&self) -> Option<Proxy> {
+        self.proxy.clone()
+    }
+
+    /// Set timeouts for the requests created by this client
+    /// ## Parameters
+    /// * `timeouts` - The [`Timeouts`] of the requests
+    pub fn set_timeouts(&mut self, timeouts: Timeouts) {
+        self.timeouts = timeouts;
+    }
+
+    /// Get timeouts of the client
+    /// ## Returns
+    /// [`Timeouts`]
+    pub fn get_timeouts(&self) -> Timeouts {
+        self.timeouts
+    }
+
+    /// Set resolver of the host names, it is shared by every request so caches persist between them
+    /// ## Parameters
+    /// * `resolver` - The [`Resolve`] implementation
+    /// ## Example
+    /// ```
+    /// use menemen::client::Client;
+    /// use menemen::dns::{CachingResolver, SystemResolver};
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    ///
+    /// let mut client = Client::new();
+    /// client.set_resolver(Arc::new(CachingResolver::new(
+    ///     Arc::new(SystemResolver),
+    ///     Duration::from_secs(60),
+    /// )));
+    /// ```
+    pub fn set_resolver(&mut self, resolver: Arc<dyn Resolve>) {
+        self.resolver = resolver;
+    }
+
+    /// Set address families to connect for the requests created by this client
+    /// ## Parameters
+    /// * `address_family` - The [`AddressFamily`] of the addresses to try
+    pub fn set_address_family(&mut self, address_family: AddressFamily) {
+        self.address_family = address_family;
+    }
+
+    /// Set options applied to the sockets of the requests created by this client
+    /// ## Parameters
+    /// * `socket_options` - The [`SocketOptions`] of the connections
+    pub fn set_socket_options(&mut self, socket_options: SocketOptions) {
+        self.socket_options = socket_options;
+    }
+
+    /// Set connector which opens the streams of the requests created by this client
+    /// ## Parameters
+    /// * `connector` - The [`Connector`] implementation
+    pub fn set_connector(&mut self, connector: Arc<dyn Connector>) {
+        self.connector = Some(connector);
+    }
+
+    /// Set whether the response bodies of the requests created by this client are decoded from their content encodings
+    /// ## Parameters
+    /// * `enabled` - Whether the response bodies should be decoded
+    pub fn set_decompress(&mut self, enabled: bool) {
+        self.decompress = enabled;
+    }
+
+    /// Set limits applied while reading the responses of the requests created by this client
+    /// ## Parameters
+    /// * `limits` - The [`ResponseLimits`] of the responses
+    pub fn set_limits(&mut self, limits: ResponseLimits) {
+        self.limits = limits;
+    }
+
+    /// Set cookie jar shared by the requests created by this client, so sessions persist between them
+    /// ## Parameters
+    /// * `cookie_jar` - The [`CookieJar`], it can be kept to inspect or add cookies
+    /// ## Example
+    /// ```
+    /// use menemen::client::Client;
+    /// use menemen::cookie::CookieJar;
+    /// use std::sync::Arc;
+    ///
+    /// let jar = Arc::new(CookieJar::new());
+    /// let mut client = Client::new();
+    /// client.set_cookie_jar(jar.clone());
+    /// assert!(jar.cookies().is_empty());
+    /// ```
+    pub fn set_cookie_jar(&mut self, cookie_jar: Arc<CookieJar>) {
+        self.cookie_jar = Some(cookie_jar);
+    }
+
+    /// Set Digest credentials of the requests created by this client, after the first challenge
+    /// the requests send the credentials without waiting for a `401` response
+    /// ## Parameters
+    /// * `digest_auth` - The [`DigestAuth`] credentials
+    pub fn set_digest_auth(&mut self, digest_auth: DigestAuth) {
+        self.digest_auth = Some(digest_auth);
+    }
+
+    /// Create a new [`Request`] with the configuration of this client
+    /// ## Parameters
+    /// * `url` - The url to send the request to
+    /// * `request_type` - The type of request to send takes [`RequestTypes`]
+    /// ## Returns
+    /// [`Request`] if the request was successfully created else [`anyhow::Error`]
+    /// ## Example
+    /// ```
+    /// use menemen::client::Client;
+    /// use menemen::proxy::Proxy;
+    /// use menemen::request::RequestTypes;
+    ///
+    /// let mut client = Client::new();
+    /// client.set_proxy(Proxy::new("http://127.0.0.1:3128").unwrap());
+    /// let request = client.request("http://behemehal.org", RequestTypes::GET).unwrap();
+    /// assert!(request.get_proxy().is_some());
+    /// ```
+    pub fn request(&self, url: &str, request_type: RequestTypes) -> anyhow::Result<Request> {
+        let mut request = Request::new(url, request_type)?;
+        if let Some(proxy) = &self.proxy {
+            request.set_proxy(proxy.clone());
+        }
+        request.set_timeouts(self.timeouts);
+        request.set_resolver(self.resolver.clone());
+        request.set_address_family(self.address_family);
+        request.set_socket_options(self.socket_options.clone());
+        if let Some(connector) = &self.connector {
+            request.set_connector(connector.clone());
+        }
+        if !self.env_proxy {
+            request.set_env_proxy(false);
+        }
+        if !self.decompress {
+            request.set_decompress(false);
+        }
+        request.set_limits(self.limits);
+        if let Some(cookie_jar) = &self.cookie_jar {
+            request.set_cookie_jar(cookie_jar.clone());
+        }
+        if let Some(digest_auth) = &self.digest_auth {
+            request.set_digest_auth(digest_auth.clone());
+        }
+        Ok(request)
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{
+    dns::{Resolve, SystemResolver},
+    error,
+    proxy::{EnvProxies, Proxy},
+    transport::{self, AddressFamily, SocketOptions, Timeouts, Transport},
+    url::Url,
+};
+use bufstream::BufStream;
+use native_tls::TlsConnector;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::{
+    fmt::Debug,
+    io::{BufRead, Read, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+
+/// Streams that can carry an http exchange
+pub trait Stream: Read + Write + BufRead + Send + Sync {}
+
+impl<T: Read + Write + BufRead + Send + Sync> Stream for T {}
+
+/// Opens the stream a request is sent over
+pub trait Connector: Debug + Send + Sync {
+    /// Open a stream to the target of the request
+    /// ## Parameters
+    /// * `url` - The [`Url`] of the request
+    /// ## Returns
+    /// Stream which the request will be written to and the response read from else [`error::RequestErrors`]
+    fn connect(&self, url: &Url) -> Result<Box<dyn Stream>, error::RequestErrors>;
+}
+
+/// Connector that opens tcp, tls, proxied and unix socket connections with the request configuration,
+/// used when no other [`Connector`] is set. Get one with [`crate::request::Request::default_connector`] to wrap it
+#[derive(Clone, Debug)]
+pub struct DefaultConnector {
+    /// Timeouts of the request [`Timeouts`]
+    pub(crate) timeouts: Timeouts,
+    /// Instant the request should be completed, shared by the redirects
+    pub(crate) deadline: Option<Instant>,
+    /// Resolver of the host names [`Resolve`]
+    pub(crate) resolver: Arc<dyn Resolve>,
+    /// Address families to connect [`AddressFamily`]
+    pub(crate) address_family: AddressFamily,
+    /// Options of the socket [`SocketOptions`]
+    pub(crate) socket_options: SocketOptions,
+    /// Proxy the request is routed through [`Proxy`]
+    pub(crate) proxy: Option<Proxy>,
+    /// Proxies read from environment, used when no proxy set [`EnvProxies`]
+    pub(crate) env_proxies: Option<EnvProxies>,
+    /// Path of the unix socket the request is sent over
+    pub(crate) unix_socket: Option<PathBuf>,
+}
+
+impl Default for DefaultConnector {
+    fn default() -> Self {
+        DefaultConnector {
+            timeouts: Timeouts::default(),
+            deadline: None,
+            resolver: Arc::new(SystemResolver),
+            address_family: AddressFamily::default(),
+            socket_options: SocketOptions::default(),
+            proxy: None,
+            env_proxies: Some(EnvProxies::from_env()),
+            unix_socket: None,
+        }
+    }
+}
+
+impl DefaultConnector {
+    /// Returns the proxy the url should be routed through, if any
+    pub(crate) fn active_proxy(&self, url: &Url) -> Result<Option<&Proxy>, error::RequestErrors> {
+        if self.unix_socket.is_some() {
+            return Ok(None);
+        }
+        match (&self.proxy, &self.env_proxies) {
+            (Some(proxy), _) => Ok(Some(proxy).filter(|proxy| !proxy.is_bypassed(url))),
+            (None, Some(env_proxies)) => env_proxies
+                .proxy_for(url)
+                .map_err(|e| error::RequestErrors::ConnectionError(e.to_string())),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Opens a connection to the server, or to the proxy in front of it
+    /// ## Parameters
+    /// * `url` - The [`Url`] of the request
+    /// ## Returns
+    /// [`Transport`] if connection established else [`error::RequestErrors`]
+    pub fn connect_transport(&self, url: &Url) -> Result<Transport, error::RequestErrors> {
+        let deadline = self
+            .deadline
+            .or_else(|| self.timeouts.total.map(|x| Instant::now() + x));
+        if let Some(path) = &self.unix_socket {
+            return self.connect_unix(path, url, deadline);
+        }
+        let proxy = self.active_proxy(url)?;
+        let socket_addr = match proxy {
+            Some(proxy) => (proxy.host.clone(), proxy.port),
+            None => (url.host.clone(), url.port),
+        };
+        let mut tcp_stream = transport::connect_tcp(
+            &socket_addr.0,
+            socket_addr.1,
+            self.resolver.as_ref(),
+            self.address_family,
+            &self.socket_options,
+            &self.timeouts,
+            deadline,
+        )?;
+
+        if let Some(proxy) = proxy {
+            proxy.handshake(
+                &mut tcp_stream,
+                url,
+                self.resolver.as_ref(),
+                self.address_family,
+            )?;
+        }
+
+        if url.is_https {
+            let tls_stream = TlsConnector::new()
+                .map_err(|e| error::RequestErrors::ConnectionError(e.to_string()))?
+                .connect(&url.host, tcp_stream)
+                .map_err(|e| error::RequestErrors::ConnectionError(e.to_string()))?;
+            Ok(Transport::Ssl(BufStream::new(tls_stream)))
+        } else {
+            Ok(Transport::Tcp(BufStream::new(tcp_stream)))
+        }
+    }
+
+    /// Opens a connection to the unix socket
+    #[cfg(unix)]
+    fn connect_unix(
+        &self,
+        path: &Path,
+        url: &Url,
+        deadline: Option<Instant>,
+    ) -> Result<Transport, error::RequestErrors> {
+        if url.is_https {
+            return Err(error::RequestErrors::ConnectionError(
+                "Tls over unix sockets is not supported".to_string(),
+            ));
+        }
+        let stream = UnixStream::connect(path)?;
+        Ok(Transport::Unix(BufStream::new(
+            transport::TimedStream::new(stream, &self.timeouts, deadline),
+        )))
+    }
+
+    /// Opens a connection to the unix socket
+    #[cfg(not(unix))]
+    fn connect_unix(
+        &self,
+        _path: &Path,
+        _url: &Url,
+        _deadline: Option<Instant>,
+    ) -> Result<Transport, error::RequestErrors> {
+        Err(error::RequestErrors::ConnectionError(
+            "Unix sockets are not supported on this platform".to_string(),
+        ))
+    }
+}
+
+impl Connector for DefaultConnector {
+    fn connect(&self, url: &Url) -> Result<Box<dyn Stream>, error::RequestErrors> {
+        Ok(Box::new(self.connect_transport(url)?))
+    }
+}
@@ -0,0 +1,677 @@
+use crate::{lock::lock, request::Header, url::Url};
+use anyhow::Context;
+use std::{
+    cmp::Reverse,
+    io::{self, BufRead, Write},
+    net::IpAddr,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// `SameSite` attribute of a cookie, stored but not enforced since requests have no site of origin
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SameSite {
+    /// Cookie is only sent with requests from the same site
+    Strict,
+    /// Cookie is also sent with top level navigations from other sites
+    Lax,
+    /// Cookie is sent with every request
+    None,
+}
+
+impl SameSite {
+    /// Parse the attribute value, names are compared case insensitively
+    /// ## Parameters
+    /// * `value` - Value of the `SameSite` attribute
+    /// ## Returns
+    /// [`SameSite`] if the value is known else [`None`]
+    pub fn parse(value: &str) -> Option<SameSite> {
+        match value.to_lowercase().as_str() {
+            "strict" => Some(SameSite::Strict),
+            "lax" => Some(SameSite::Lax),
+            "none" => Some(SameSite::None),
+            _ => None,
+        }
+    }
+
+    /// Get the attribute value of the [`SameSite`]
+    pub fn get_type(&self) -> &'static str {
+        match self {
+            SameSite::Strict => "Strict",
+            SameSite::Lax => "Lax",
+            SameSite::None => "None",
+        }
+    }
+}
+
+/// Cookie stored in a [`CookieJar`]
+/// #### https://www.rfc-editor.org/rfc/rfc6265
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cookie {
+    /// The name of the cookie
+    pub name: String,
+    /// The value of the cookie
+    pub value: String,
+    /// Lowercase domain the cookie is sent to, without the leading dot
+    pub domain: String,
+    /// Cookie is only sent to the exact domain, else it is also sent to the subdomains
+    pub host_only: bool,
+    /// Path the cookie is sent to along with its subpaths
+    pub path: String,
+    /// Time the cookie expires, [`None`] for session cookies
+    pub expires: Option<SystemTime>,
+    /// Cookie is only sent over https
+    pub secure: bool,
+    /// Cookie is not exposed to scripts, kept for completeness
+    pub http_only: bool,
+    /// `SameSite` attribute of the cookie [`SameSite`]
+    pub same_site: Option<SameSite>,
+}
+
+impl Cookie {
+    /// Parse `Set-Cookie` header value received from the url
+    /// ## Parameters
+    /// * `set_cookie` - The value of the `Set-Cookie` header
+    /// * `url` - The url of the request the response belongs to
+    /// ## Returns
+    /// [`Cookie`] if the cookie was parsed and the url is allowed to set it else [`anyhow::Error`]
+    /// ## Example
+    /// ```
+    /// use menemen::cookie::Cookie;
+    /// use menemen::url::Url;
+    ///
+    /// let url = Url::build_from_string("https://www.behemehal.org/account/login".to_string()).unwrap();
+    /// let cookie = Cookie::parse("session=abc; Domain=behemehal.org; Secure; HttpOnly", &url).unwrap();
+    /// assert_eq!(cookie.name, "session");
+    /// assert_eq!(cookie.domain, "behemehal.org");
+    /// assert_eq!(cookie.path, "/account");
+    /// assert!(!cookie.host_only && cookie.secure && cookie.http_only);
+    /// ```
+    pub fn parse(set_cookie: &str, url: &Url) -> anyhow::Result<Cookie> {
+        let mut attributes = set_cookie.split(';');
+        let pair = attributes.next().unwrap_or_default();
+        let (name, value) = pair
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("Cookie has no name value pair"))?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(anyhow::anyhow!("Cookie name is empty"));
+        }
+
+        let host = url.host.to_lowercase();
+        let mut cookie = Cookie {
+            name: name.to_string(),
+            value: value.trim().to_string(),
+            domain: host.clone(),
+            host_only: true,
+            path: default_path(url),
+            expires: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+        };
+        let mut max_age = None;
+        let mut expires = None;
+        for attribute in attributes {
+            let (key, value) = attribute.split_once('=').unwrap_or((attribute, ""));
+            let value = value.trim();
+            match key.trim().to_lowercase().as_str() {
+                "expires" => expires = parse_cookie_date(value).or(expires),
+                "max-age" if value.starts_with(|x: char| x.is_ascii_digit() || x == '-') => {
+                    max_age = value.parse::<i64>().ok().or(max_age);
+                }
+                "domain" => {
+                    let domain = value.trim_start_matches('.').to_lowercase();
+                    if !domain.is_empty() {
+                        cookie.domain = domain;
+                        cookie.host_only = false;
+                    }
+                }
+                "path" if value.starts_with('/') => cookie.path = value.to_string(),
+                "secure" => cookie.secure = true,
+                "httponly" => cookie.http_only = true,
+                "samesite" => cookie.same_site = SameSite::parse(value),
+                _ => {}
+            }
+        }
+        //Max-Age takes precedence over Expires
+        cookie.expires = match max_age {
+            Some(seconds) if seconds <= 0 => Some(UNIX_EPOCH),
+            Some(seconds) => Some(expiry_after(seconds as u64)),
+            None => expires,
+        };
+
+        if !cookie.host_only {
+            //Without the public suffix list, at least top level domains are refused
+            if !domain_match(&host, &cookie.domain)
+                || (!cookie.domain.contains('.') && cookie.domain != host)
+            {
+                return Err(anyhow::anyhow!(
+                    "Cookie domain '{}' is not allowed for '{}'",
+                    cookie.domain,
+                    host
+                ));
+            }
+        }
+        if cookie.secure && !url.is_https {
+            return Err(anyhow::anyhow!("Secure cookie can't be set over http"));
+        }
+        Ok(cookie)
+    }
+
+    /// Whether the cookie is expired
+    pub fn is_expired(&self) -> bool {
+        self.expires.is_some_and(|x| x <= SystemTime::now())
+    }
+
+    /// Whether the cookie should be sent with a request to the url
+    /// ## Parameters
+    /// * `url` - The url of the request
+    pub fn matches(&self, url: &Url) -> bool {
+        let host = url.host.to_lowercase();
+        let domain_matches = if self.host_only {
+            host == self.domain
+        } else {
+            domain_match(&host, &self.domain)
+        };
+        domain_matches
+            && path_match(&request_path(url), &self.path)
+            && (!self.secure || url.is_https)
+            && !self.is_expired()
+    }
+}
+
+/// Seconds since the unix epoch, times before it are clamped to the first second
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|x| x.as_secs())
+        .unwrap_or_default()
+        .max(1)
+}
+
+/// Time the seconds after now, capped at the latest time the platform can represent (RFC 6265 section 5.2.2)
+fn expiry_after(seconds: u64) -> SystemTime {
+    let now = SystemTime::now();
+    if let Some(time) = now.checked_add(Duration::from_secs(seconds)) {
+        return time;
+    }
+    //Search the largest number of seconds that still fits
+    let (mut fits, mut overflows) = (0, seconds);
+    while overflows - fits > 1 {
+        let middle = fits + (overflows - fits) / 2;
+        if now.checked_add(Duration::from_secs(middle)).is_some() {
+            fits = middle;
+        } else {
+            overflows = middle;
+        }
+    }
+    now + Duration::from_secs(fits)
+}
+
+/// Time of the seconds since the unix epoch read from a cookie file
+fn from_unix_seconds(seconds: u64) -> anyhow::Result<SystemTime> {
+    UNIX_EPOCH
+        .checked_add(Duration::from_secs(seconds))
+        .ok_or_else(|| anyhow::anyhow!("Expiry time {} is out of range", seconds))
+}
+
+/// Whether the host is the domain or its subdomain, ip addresses only match themselves
+fn domain_match(host: &str, domain: &str) -> bool {
+    host == domain
+        || (host.ends_with(domain)
+            && host[..host.len() - domain.len()].ends_with('.')
+            && host
+                .trim_matches(|x| x == '[' || x == ']')
+                .parse::<IpAddr>()
+                .is_err())
+}
+
+/// Whether the request path is the cookie path or under it
+fn path_match(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || request_path[cookie_path.len()..].starts_with('/')))
+}
+
+/// Path of the url without the query
+fn request_path(url: &Url) -> String {
+    format!("/{}", url.paths.join("/"))
+}
+
+/// Path used when the cookie has no `Path` attribute, the directory of the request path
+fn default_path(url: &Url) -> String {
+    let path = request_path(url);
+    match path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(index) => path[..index].to_string(),
+    }
+}
+
+/// Whether the character separates the tokens of a cookie date
+fn is_date_delimiter(x: char) -> bool {
+    x == '\t'
+        || (' '..='/').contains(&x)
+        || (';'..='@').contains(&x)
+        || ('['..='`').contains(&x)
+        || ('{'..='~').contains(&x)
+}
+
+/// Parses the number at the start of the token, which has `min` to `max` digits
+fn leading_number(token: &str, min: usize, max: usize) -> Option<u32> {
+    let digits = token.chars().take_while(|x| x.is_ascii_digit()).count();
+    if digits < min || digits > max {
+        return None;
+    }
+    token[..digits].parse().ok()
+}
+
+/// Parses `hh:mm:ss` token of a cookie date
+fn parse_time(token: &str) -> Option<(u32, u32, u32)> {
+    let mut fields = token.splitn(3, ':');
+    let hour = fields.next()?;
+    let minute = fields.next()?;
+    let second = fields.next()?;
+    if hour.len() > 2 || minute.len() > 2 {
+        return None;
+    }
+    Some((
+        hour.parse().ok()?,
+        minute.parse().ok()?,
+        leading_number(second, 1, 2)?,
+    ))
+}
+
+/// Parses the date of `Expires` attribute, accepting the formats servers use in practice
+/// #### https://www.rfc-editor.org/rfc/rfc6265#section-5.1.1
+fn parse_cookie_date(value: &str) -> Option<SystemTime> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let mut time = None;
+    let mut day = None;
+    let mut month = None;
+    let mut year = None;
+    for token in value.split(is_date_delimiter).filter(|x| !x.is_empty()) {
+        if time.is_none() {
+            if let Some(found) = parse_time(token) {
+                time = Some(found);
+                continue;
+            }
+        }
+        if day.is_none() {
+            if let Some(found) = leading_number(token, 1, 2) {
+                day = Some(found);
+                continue;
+            }
+        }
+        if month.is_none() {
+            let prefix = token.get(..3).unwrap_or_default().to_lowercase();
+            if let Some(found) = MONTHS.iter().position(|x| *x == prefix) {
+                month = Some(found as u32 + 1);
+                continue;
+            }
+        }
+        if year.is_none() {
+            if let Some(found) = leading_number(token, 2, 4) {
+                year = Some(found);
+            }
+        }
+    }
+
+    let (hour, minute, second) = time?;
+    let (day, month, year) = (day?, month?, year?);
+    let year = match year {
+        70..=99 => year + 1900,
+        0..=69 => year + 2000,
+        _ => year,
+    };
+    if !(1..=31).contains(&day) || year < 1601 || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+    let seconds = days_from_civil(year as i64, month, day) * 86400
+        + (hour * 3600 + minute * 60 + second) as i64;
+    if seconds < 0 {
+        return Some(UNIX_EPOCH);
+    }
+    UNIX_EPOCH.checked_add(Duration::from_secs(seconds as u64))
+}
+
+/// Number of days from 1970-01-01 to the date of the proleptic gregorian calendar
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Store of the cookies, shared by the requests through [`crate::client::Client::set_cookie_jar`]
+#[derive(Debug, Default)]
+pub struct CookieJar {
+    /// Cookies in the order they are created
+    cookies: Mutex<Vec<Cookie>>,
+}
+
+impl CookieJar {
+    /// Create a new empty [`CookieJar`]
+    pub fn new() -> CookieJar {
+        CookieJar::default()
+    }
+
+    /// Stores the cookie, replacing the one with the same name, domain and path.
+    /// An expired cookie removes the stored one
+    /// ## Parameters
+    /// * `cookie` - The [`Cookie`] to store
+    pub fn insert(&self, cookie: Cookie) {
+        let mut cookies = lock(&self.cookies);
+        let existing = cookies.iter().position(|x| {
+            x.name == cookie.name && x.domain == cookie.domain && x.path == cookie.path
+        });
+        match existing {
+            Some(index) if cookie.is_expired() => {
+                cookies.remove(index);
+            }
+            //Replaced cookie keeps its place, so the creation order stays
+            Some(index) => cookies[index] = cookie,
+            None if cookie.is_expired() => {}
+            None => cookies.push(cookie),
+        }
+    }
+
+    /// Parses and stores the `Set-Cookie` header value received from the url
+    /// ## Parameters
+    /// * `set_cookie` - The value of the `Set-Cookie` header
+    /// * `url` - The url of the request the response belongs to
+    /// ## Returns
+    /// `Ok(())` if the cookie was stored else [`anyhow::Error`]
+    /// ## Example
+    /// ```
+    /// use menemen::cookie::CookieJar;
+    /// use menemen::url::Url;
+    ///
+    /// let jar = CookieJar::new();
+    /// let url = Url::build_from_string("http://behemehal.org/".to_string()).unwrap();
+    /// jar.set_cookie("lang=tr; Path=/", &url).unwrap();
+    /// assert_eq!(jar.cookie_header(&url).unwrap(), "lang=tr");
+    /// ```
+    pub fn set_cookie(&self, set_cookie: &str, url: &Url) -> anyhow::Result<()> {
+        self.insert(Cookie::parse(set_cookie, url)?);
+        Ok(())
+    }
+
+    /// Stores the cookies of the `Set-Cookie` headers of a response, invalid ones are ignored
+    /// ## Parameters
+    /// * `url` - The url of the request the response belongs to
+    /// * `headers` - Headers of the response
+    pub fn store_response_cookies(&self, url: &Url, headers: &[Header]) {
+        for header in headers
+            .iter()
+            .filter(|x| x.name.eq_ignore_ascii_case("Set-Cookie"))
+        {
+            let _ = self.set_cookie(&header.value, url);
+        }
+    }
+
+    /// Get the stored cookies which are not expired
+    pub fn cookies(&self) -> Vec<Cookie> {
+        let mut cookies = lock(&self.cookies);
+        cookies.retain(|x| !x.is_expired());
+        cookies.clone()
+    }
+
+    /// Get the cookies sent with a request to the url, longer paths first
+    /// ## Parameters
+    /// * `url` - The url of the request
+    pub fn cookies_for(&self, url: &Url) -> Vec<Cookie> {
+        let mut cookies = self
+            .cookies()
+            .into_iter()
+            .filter(|x| x.matches(url))
+            .collect::<Vec<_>>();
+        //Stable sort keeps older cookies first among the same path lengths
+        cookies.sort_by_key(|x| Reverse(x.path.len()));
+        cookies
+    }
+
+    /// Get the value of the `Cookie` header of a request to the url
+    /// ## Parameters
+    /// * `url` - The url of the request
+    /// ## Returns
+    /// Header value if any cookie matches the url else [`None`]
+    pub fn cookie_header(&self, url: &Url) -> Option<String> {
+        let cookies = self.cookies_for(url);
+        if cookies.is_empty() {
+            return None;
+        }
+        Some(
+            cookies
+                .iter()
+                .map(|x| format!("{}={}", x.name, x.value))
+                .collect::<Vec<_>>()
+                .join("; "),
+        )
+    }
+
+    /// Removes every cookie
+    pub fn clear(&self) {
+        lock(&self.cookies).clear();
+    }
+
+    /// Writes the cookies in Netscape `cookies.txt` format used by curl and wget, expired cookies are skipped
+    /// ## Parameters
+    /// * `writer` - The [`std::io::Write`] the cookies are written to
+    /// * `include_session` - Write the session cookies too, which have no expiry time
+    /// ## Returns
+    /// `Ok(())` if the cookies were written else [`std::io::Error`]
+    /// ## Example
+    /// ```no_run
+    /// use menemen::cookie::CookieJar;
+    ///
+    /// let jar = CookieJar::new();
+    /// let mut file = std::fs::File::create("cookies.txt").unwrap();
+    /// jar.save_netscape(&mut file, false).unwrap();
+    /// ```
+    pub fn save_netscape<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        include_session: bool,
+    ) -> io::Result<()> {
+        writeln!(writer, "# Netscape HTTP Cookie File")?;
+        for cookie in self.cookies() {
+            if cookie.expires.is_none() && !include_session {
+                continue;
+            }
+            let bool_field = |x: bool| if x { "TRUE" } else { "FALSE" };
+            writeln!(
+                writer,
+                "{http_only}{dot}{domain}\t{subdomains}\t{path}\t{secure}\t{expires}\t{name}\t{value}",
+                http_only = if cookie.http_only { "#HttpOnly_" } else { "" },
+                dot = if cookie.host_only { "" } else { "." },
+                domain = cookie.domain,
+                subdomains = bool_field(!cookie.host_only),
+                path = cookie.path,
+                secure = bool_field(cookie.secure),
+                expires = cookie.expires.map(unix_seconds).unwrap_or_default(),
+                name = cookie.name,
+                value = cookie.value,
+            )?;
+        }
+        writer.flush()
+    }
+
+    /// Loads the cookies from Netscape `cookies.txt` format, expired cookies are skipped
+    /// ## Parameters
+    /// * `reader` - The [`std::io::BufRead`] the cookies are read from
+    /// * `include_session` - Load the session cookies too, which have `0` as expiry time
+    /// ## Returns
+    /// `Ok(())` if the cookies were loaded else [`anyhow::Error`] with the malformed line
+    /// ## Example
+    /// ```
+    /// use menemen::cookie::CookieJar;
+    ///
+    /// let jar = CookieJar::new();
+    /// let file = "# Netscape HTTP Cookie File\n\
+    ///     .behemehal.org\tTRUE\t/\tFALSE\t4102444800\tlang\ttr\n";
+    /// jar.load_netscape(file.as_bytes(), false).unwrap();
+    /// assert_eq!(jar.cookies()[0].domain, "behemehal.org");
+    /// ```
+    pub fn load_netscape<R: BufRead>(
+        &self,
+        reader: R,
+        include_session: bool,
+    ) -> anyhow::Result<()> {
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim_end_matches(['\r', '\n']);
+            let (http_only, line) = match line.strip_prefix("#HttpOnly_") {
+                Some(line) => (true, line),
+                None => (false, line),
+            };
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields = line.split('\t').collect::<Vec<_>>();
+            let malformed = || format!("Malformed cookie at line {}", index + 1);
+            if fields.len() < 6 || fields.len() > 7 {
+                return Err(anyhow::anyhow!(malformed()));
+            }
+            let expires = match fields[4].parse::<u64>().with_context(malformed)? {
+                0 => None,
+                seconds => Some(from_unix_seconds(seconds).with_context(malformed)?),
+            };
+            if expires.is_none() && !include_session {
+                continue;
+            }
+            self.insert(Cookie {
+                name: fields[5].to_string(),
+                value: fields.get(6).unwrap_or(&"").to_string(),
+                domain: fields[0].trim_start_matches('.').to_lowercase(),
+                host_only: !fields[1].eq_ignore_ascii_case("TRUE"),
+                path: fields[2].to_string(),
+                expires,
+                secure: fields[3].eq_ignore_ascii_case("TRUE"),
+                http_only,
+                same_site: None,
+            });
+        }
+        Ok(())
+    }
+
+    /// Writes the cookies as a JSON array of objects, expired cookies are skipped.
+    /// `expires` field is seconds since the unix epoch, `null` for session cookies
+    /// ## Parameters
+    /// * `writer` - The [`std::io::Write`] the cookies are written to
+    /// * `include_session` - Write the session cookies too
+    /// ## Returns
+    /// `Ok(())` if the cookies were written else [`anyhow::Error`]
+    /// ## Example
+    /// ```no_run
+    /// use menemen::cookie::CookieJar;
+    ///
+    /// let jar = CookieJar::new();
+    /// let mut file = std::fs::File::create("cookies.json").unwrap();
+    /// jar.save_json(&mut file, true).unwrap();
+    /// ```
+    #[cfg(feature = "serde")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+    pub fn save_json<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        include_session: bool,
+    ) -> anyhow::Result<()> {
+        let cookies = self
+            .cookies()
+            .into_iter()
+            .filter(|x| x.expires.is_some() || include_session)
+            .map(|x| {
+                serde_json::json!({
+                    "name": x.name,
+                    "value": x.value,
+                    "domain": x.domain,
+                    "host_only": x.host_only,
+                    "path": x.path,
+                    "expires": x.expires.map(unix_seconds),
+                    "secure": x.secure,
+                    "http_only": x.http_only,
+                    "same_site": x.same_site.map(|x| x.get_type()),
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::to_writer_pretty(&mut *writer, &cookies)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Loads the cookies from the JSON format written by [`CookieJar::save_json`], expired cookies are skipped
+    /// ## Parameters
+    /// * `reader` - The [`std::io::Read`] the cookies are read from
+    /// * `include_session` - Load the session cookies too
+    /// ## Returns
+    /// `Ok(())` if the cookies were loaded else [`anyhow::Error`]
+    /// ## Example
+    /// ```
+    /// use menemen::cookie::CookieJar;
+    ///
+    /// let jar = CookieJar::new();
+    /// let file = r#"[{"name": "lang", "value": "tr", "domain": "behemehal.org", "expires": null}]"#;
+    /// jar.load_json(file.as_bytes(), true).unwrap();
+    /// assert!(jar.cookies()[0].host_only);
+    /// ```
+    #[cfg(feature = "serde")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+    pub fn load_json<R: io::Read>(&self, reader: R, include_session: bool) -> anyhow::Result<()> {
+        use serde_json::Value;
+
+        let cookies = match serde_json::from_reader(reader)? {
+            Value::Array(cookies) => cookies,
+            _ => return Err(anyhow::anyhow!("Cookies should be a JSON array")),
+        };
+        for (index, cookie) in cookies.iter().enumerate() {
+            //Missing and null fields take their defaults, fields of another type make the entry malformed
+            let field = |key: &str| cookie.get(key).filter(|x| !x.is_null());
+            let malformed = |key: &str| anyhow::anyhow!("Cookie {} has invalid {}", index, key);
+            let text = |key: &str| {
+                field(key)
+                    .map(|x| x.as_str().ok_or_else(|| malformed(key)))
+                    .transpose()
+            };
+            let flag = |key: &str| {
+                field(key)
+                    .map(|x| x.as_bool().ok_or_else(|| malformed(key)))
+                    .transpose()
+            };
+            let (Some(name), Some(value), Some(domain)) =
+                (text("name")?, text("value")?, text("domain")?)
+            else {
+                return Err(anyhow::anyhow!(
+                    "Cookie {} should have name, value and domain",
+                    index
+                ));
+            };
+            let expires = field("expires")
+                .map(|x| x.as_u64().ok_or_else(|| malformed("expires")))
+                .transpose()?
+                .map(from_unix_seconds)
+                .transpose()
+                .with_context(|| format!("Cookie {} has invalid expiry time", index))?;
+            if expires.is_none() && !include_session {
+                continue;
+            }
+            self.insert(Cookie {
+                name: name.to_string(),
+                value: value.to_string(),
+                domain: domain.trim_start_matches('.').to_lowercase(),
+                host_only: flag("host_only")?.unwrap_or(true),
+                path: text("path")?.unwrap_or("/").to_string(),
+                expires,
+                secure: flag("secure")?.unwrap_or_default(),
+                http_only: flag("http_only")?.unwrap_or_default(),
+                same_site: text("same_site")?.and_then(SameSite::parse),
+            });
+        }
+        Ok(())
+    }
+}
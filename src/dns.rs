@@ -0,0 +1,159 @@
+use crate::lock::lock;
+use anyhow::Context;
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    io,
+    net::{IpAddr, SocketAddr, ToSocketAddrs},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Resolves host names to socket addresses
+pub trait Resolve: Debug + Send + Sync {
+    /// Resolve host and port to the list of addresses
+    /// ## Parameters
+    /// * `host` - Host name or ip address
+    /// * `port` - Port number
+    /// ## Returns
+    /// [`Vec<SocketAddr>`] in the order they should be tried else [`io::Error`]
+    fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>>;
+}
+
+/// Resolver of the operating system
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemResolver;
+
+impl Resolve for SystemResolver {
+    fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        Ok((host, port).to_socket_addrs()?.collect())
+    }
+}
+
+/// Resolver that answers pinned host and port pairs like curl's `--resolve` and asks the fallback for the rest
+#[derive(Debug)]
+pub struct StaticResolver {
+    overrides: HashMap<(String, u16), Vec<IpAddr>>,
+    fallback: Arc<dyn Resolve>,
+}
+
+impl StaticResolver {
+    /// Create a new [`StaticResolver`]
+    /// ## Parameters
+    /// * `fallback` - Resolver of the hosts without override
+    pub fn new(fallback: Arc<dyn Resolve>) -> StaticResolver {
+        StaticResolver {
+            overrides: HashMap::new(),
+            fallback,
+        }
+    }
+
+    /// Pin host and port to given addresses
+    /// ## Parameters
+    /// * `host` - Host name
+    /// * `port` - Port number
+    /// * `addresses` - Addresses the host resolves to
+    pub fn add(&mut self, host: &str, port: u16, addresses: Vec<IpAddr>) {
+        self.overrides
+            .insert((host.to_lowercase(), port), addresses);
+    }
+
+    /// Pin host and port with curl's `host:port:addr[,addr]...` syntax, ipv6 addresses can be wrapped in brackets
+    /// ## Parameters
+    /// * `entry` - Override entry like `staging.behemehal.org:443:10.0.0.5`
+    /// ## Returns
+    /// [`anyhow::Error`] if the entry is malformed
+    /// ## Example
+    /// ```
+    /// use menemen::dns::{Resolve, StaticResolver, SystemResolver};
+    /// use std::sync::Arc;
+    ///
+    /// let mut resolver = StaticResolver::new(Arc::new(SystemResolver));
+    /// resolver.add_entry("staging.behemehal.org:443:10.0.0.5,[::1]").unwrap();
+    /// let addresses = resolver.resolve("staging.behemehal.org", 443).unwrap();
+    /// assert_eq!(addresses[0].to_string(), "10.0.0.5:443");
+    /// assert_eq!(addresses[1].to_string(), "[::1]:443");
+    /// ```
+    pub fn add_entry(&mut self, entry: &str) -> anyhow::Result<()> {
+        let mut parts = entry.splitn(3, ':');
+        let (host, port, addresses) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(host), Some(port), Some(addresses)) if !host.is_empty() => {
+                (host, port, addresses)
+            }
+            _ => return Err(anyhow::anyhow!("Resolve entry should be host:port:addr")),
+        };
+        let port = port
+            .parse::<u16>()
+            .with_context(|| "Failed to parse port")?;
+        let addresses = addresses
+            .split(',')
+            .map(|x| {
+                x.trim()
+                    .trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .parse::<IpAddr>()
+                    .with_context(|| format!("Failed to parse address '{}'", x))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        self.add(host, port, addresses);
+        Ok(())
+    }
+}
+
+impl Resolve for StaticResolver {
+    fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        match self.overrides.get(&(host.to_lowercase(), port)) {
+            Some(addresses) => Ok(addresses
+                .iter()
+                .map(|ip| SocketAddr::new(*ip, port))
+                .collect()),
+            None => self.fallback.resolve(host, port),
+        }
+    }
+}
+
+/// Cached answers with their expiry instant, keyed by host and port
+type AnswerCache = HashMap<(String, u16), (Instant, Vec<SocketAddr>)>;
+
+/// Resolver that keeps answers of the inner resolver in memory for a fixed time
+#[derive(Debug)]
+pub struct CachingResolver {
+    inner: Arc<dyn Resolve>,
+    ttl: Duration,
+    cache: Mutex<AnswerCache>,
+}
+
+impl CachingResolver {
+    /// Create a new [`CachingResolver`]
+    /// ## Parameters
+    /// * `inner` - Resolver which answers are cached
+    /// * `ttl` - How long answers are kept
+    pub fn new(inner: Arc<dyn Resolve>, ttl: Duration) -> CachingResolver {
+        CachingResolver {
+            inner,
+            ttl,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Remove every cached answer
+    pub fn clear(&self) {
+        lock(&self.cache).clear();
+    }
+}
+
+impl Resolve for CachingResolver {
+    fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        let key = (host.to_lowercase(), port);
+        if let Some((expires, addresses)) = lock(&self.cache).get(&key) {
+            if *expires > Instant::now() {
+                return Ok(addresses.clone());
+            }
+        }
+        let addresses = self.inner.resolve(host, port)?;
+        let mut cache = lock(&self.cache);
+        cache.retain(|_, (expires, _)| *expires > Instant::now());
+        cache.insert(key, (Instant::now() + self.ttl, addresses.clone()));
+        Ok(addresses)
+    }
+}
@@ -0,0 +1,99 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes bytes to standard padded base64
+pub(crate) fn base64_encode(input: &[u8]) -> String {
+    let mut output = String::with_capacity(input.len().div_ceil(3) * 4);
+    for chunk in input.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let triple = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                output.push(BASE64_ALPHABET[(triple >> (18 - i * 6) & 0x3f) as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+/// Builds the `Basic` credentials value used by `Authorization` and `Proxy-Authorization`
+pub(crate) fn basic_credentials(username: &str, password: &str) -> String {
+    format!(
+        "Basic {}",
+        base64_encode(format!("{}:{}", username, password).as_bytes())
+    )
+}
+
+/// Percent-encodes the value as `application/x-www-form-urlencoded` does, space becomes `+`
+pub(crate) fn form_urlencode(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'*' | b'-' | b'.' | b'_' => {
+                output.push(byte as char)
+            }
+            b' ' => output.push('+'),
+            _ => output.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    output
+}
+
+/// Decodes `application/x-www-form-urlencoded` value, malformed escapes are kept as they are
+pub(crate) fn form_urldecode(input: &str) -> String {
+    percent_decode(&input.replace('+', " "))
+}
+
+/// Decodes the percent escapes of the value, malformed escapes are kept as they are
+pub(crate) fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut output = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'%' => match input
+                .get(index + 1..index + 3)
+                .filter(|x| x.bytes().all(|x| x.is_ascii_hexdigit()))
+                .and_then(|x| u8::from_str_radix(x, 16).ok())
+            {
+                Some(byte) => {
+                    output.push(byte);
+                    index += 2;
+                }
+                None => output.push(b'%'),
+            },
+            byte => output.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&output).to_string()
+}
+
+/// Joins the name value pairs percent-encoded with `&`
+pub(crate) fn form_urlencode_pairs<'a, I>(pairs: I) -> String
+where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    pairs
+        .into_iter()
+        .map(|(name, value)| format!("{}={}", form_urlencode(name), form_urlencode(value)))
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+/// Random 32 digit hex string, used for multipart boundaries and digest client nonces
+pub(crate) fn random_hex() -> String {
+    let random = || RandomState::new().build_hasher().finish();
+    format!("{:016x}{:016x}", random(), random())
+}
@@ -1,3 +1,61 @@
+use crate::{
+    http::StatusCode,
+    request::Header,
+    response::{Response, ResponseInfo},
+};
+
+/// Phases of the request that can time out
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimeoutPhase {
+    /// Establishing the connection took longer than connect timeout
+    Connect,
+    /// Writing to the connection took longer than write timeout
+    Write,
+    /// Server sent nothing longer than read timeout
+    Read,
+    /// Whole request took longer than total timeout
+    Deadline,
+}
+
+impl std::fmt::Display for TimeoutPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeoutPhase::Connect => write!(f, "connect timed out"),
+            TimeoutPhase::Write => write!(f, "write timed out"),
+            TimeoutPhase::Read => write!(f, "read timed out"),
+            TimeoutPhase::Deadline => write!(f, "request deadline exceeded"),
+        }
+    }
+}
+
+impl std::error::Error for TimeoutPhase {}
+
+/// Response limits that can be exceeded, configured with [`crate::response::ResponseLimits`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LimitKind {
+    /// Status line is longer than allowed
+    StatusLine,
+    /// Headers take more bytes than allowed
+    HeaderBytes,
+    /// Server sent more headers than allowed
+    HeaderCount,
+    /// Decoded body is larger than allowed
+    BodySize,
+}
+
+impl std::fmt::Display for LimitKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LimitKind::StatusLine => write!(f, "status line too long"),
+            LimitKind::HeaderBytes => write!(f, "response headers too large"),
+            LimitKind::HeaderCount => write!(f, "too many response headers"),
+            LimitKind::BodySize => write!(f, "response body too large"),
+        }
+    }
+}
+
+impl std::error::Error for LimitKind {}
+
 /// List of request errors
 #[derive(Clone, Debug)]
 pub enum RequestErrors {
@@ -5,12 +63,76 @@ pub enum RequestErrors {
     CantSetHeadersAfterRequestSent,
     /// Cannot resolve given url
     CantResolveUrl,
-    /// Connection timed out
-    ConnectionTimeout,
+    /// Connection timed out in the [`TimeoutPhase`]
+    ConnectionTimeout(TimeoutPhase),
     /// Given url is not correct
     MalformedUrl,
     /// Request already sent
     AlreadySent,
     /// Connection error occured with string
     ConnectionError(String),
+    /// Response exceeded the [`LimitKind`] limit
+    LimitExceeded(LimitKind),
+    /// Body could not be serialized to or deserialized from JSON
+    JsonError {
+        /// Status code of the response, [`None`] when the request body failed to serialize
+        status_code: Option<StatusCode>,
+        /// Error of the serializer
+        message: String,
+        /// Start of the response body that failed to deserialize
+        excerpt: String,
+    },
+    /// Proxy refused to open a tunnel, contains the proxy's response
+    ProxyError {
+        /// Status line of the proxy response
+        response_info: ResponseInfo,
+        /// Headers of the proxy response
+        headers: Vec<Header>,
+    },
+}
+
+impl From<std::io::Error> for RequestErrors {
+    fn from(error: std::io::Error) -> Self {
+        if let Some(limit) = error.get_ref().and_then(|e| e.downcast_ref::<LimitKind>()) {
+            return RequestErrors::LimitExceeded(*limit);
+        }
+        let phase = error
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<TimeoutPhase>())
+            .copied();
+        match (phase, error.kind()) {
+            (Some(phase), _) => RequestErrors::ConnectionTimeout(phase),
+            (None, std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock) => {
+                RequestErrors::ConnectionTimeout(TimeoutPhase::Read)
+            }
+            _ => RequestErrors::ConnectionError(error.to_string()),
+        }
+    }
+}
+
+/// Response with a 4xx or 5xx status, returned by [`Response::error_for_status`]
+#[derive(Debug)]
+pub struct StatusError {
+    /// The failed response, its body can still be read
+    pub response: Box<Response>,
+}
+
+impl StatusError {
+    /// Get status code of the response
+    pub fn status(&self) -> StatusCode {
+        self.response.response_info.status_code
+    }
+}
+
+impl std::fmt::Display for StatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = if self.status().is_client_error() {
+            "client error"
+        } else {
+            "server error"
+        };
+        write!(f, "HTTP status {} ({})", kind, self.status())
+    }
 }
+
+impl std::error::Error for StatusError {}
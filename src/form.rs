@@ -0,0 +1,129 @@
+use crate::encoding;
+
+/// `application/x-www-form-urlencoded` body, encoded the same way as url query parameters
+/// ## Example
+/// ```
+/// use menemen::form::Form;
+///
+/// let mut form = Form::new();
+/// form.add("user", "behemehal");
+/// form.add("pass", "p@ss word");
+/// assert_eq!(form.encode(), "user=behemehal&pass=p%40ss+word");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Form {
+    pairs: Vec<(String, String)>,
+}
+
+impl Form {
+    /// Create a new empty [`Form`]
+    pub fn new() -> Form {
+        Form::default()
+    }
+
+    /// Add a field to the form, same name can be added more than once
+    /// ## Parameters
+    /// * `name` - The name of the field
+    /// * `value` - The value of the field
+    pub fn add(&mut self, name: &str, value: &str) {
+        self.pairs.push((name.to_string(), value.to_string()));
+    }
+
+    /// Get fields of the form in the order they are added
+    pub fn fields(&self) -> &[(String, String)] {
+        &self.pairs
+    }
+
+    /// Percent-encodes the fields
+    /// ## Returns
+    /// Encoded body like `name=value&other=value`
+    pub fn encode(&self) -> String {
+        encoding::form_urlencode_pairs(
+            self.pairs
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str())),
+        )
+    }
+
+    /// Builds a form from a serializable struct or map, sequences become repeated fields and [`None`] fields are skipped
+    /// ## Parameters
+    /// * `value` - The value to serialize, nested structs are not supported
+    /// ## Returns
+    /// [`Form`] if the value was serialized else [`anyhow::Error`]
+    /// ## Example
+    /// ```
+    /// use menemen::form::Form;
+    ///
+    /// #[derive(serde::Serialize)]
+    /// struct Login {
+    ///     user: String,
+    ///     remember: bool,
+    /// }
+    ///
+    /// let form = Form::from_serialize(&Login { user: "behemehal".to_string(), remember: true }).unwrap();
+    /// assert_eq!(form.encode(), "user=behemehal&remember=true");
+    /// ```
+    #[cfg(feature = "serde")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+    pub fn from_serialize<T: serde::Serialize>(value: &T) -> anyhow::Result<Form> {
+        use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+        use serde_json::Value;
+        use std::fmt;
+
+        /// Fields of the object in the order they are serialized
+        struct Fields(Vec<(String, Value)>);
+
+        impl<'de> Deserialize<'de> for Fields {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct FieldsVisitor;
+
+                impl<'de> Visitor<'de> for FieldsVisitor {
+                    type Value = Fields;
+
+                    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                        formatter.write_str("a struct or map")
+                    }
+
+                    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Fields, A::Error> {
+                        let mut fields = Vec::new();
+                        while let Some(field) = map.next_entry()? {
+                            fields.push(field);
+                        }
+                        Ok(Fields(fields))
+                    }
+                }
+
+                deserializer.deserialize_map(FieldsVisitor)
+            }
+        }
+
+        /// Converts a scalar to the field value
+        fn scalar(name: &str, value: Value) -> anyhow::Result<Option<String>> {
+            match value {
+                Value::Null => Ok(None),
+                Value::String(value) => Ok(Some(value)),
+                Value::Bool(value) => Ok(Some(value.to_string())),
+                Value::Number(value) => Ok(Some(value.to_string())),
+                _ => Err(anyhow::anyhow!("Field '{}' can't be form encoded", name)),
+            }
+        }
+
+        //Going through the text keeps the declaration order, `Value::Object` would sort the fields
+        let text = serde_json::to_string(value)?;
+        let Fields(object) = serde_json::from_str(&text)
+            .map_err(|_| anyhow::anyhow!("Only structs and maps can be form encoded"))?;
+        let mut form = Form::new();
+        for (name, value) in object {
+            let values = match value {
+                Value::Array(values) => values,
+                value => vec![value],
+            };
+            for value in values {
+                if let Some(value) = scalar(&name, value)? {
+                    form.add(&name, &value);
+                }
+            }
+        }
+        Ok(form)
+    }
+}
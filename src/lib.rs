@@ -9,26 +9,46 @@
 //!
 //!## Usage
 //!
-//! ```
-//! use std::io::{Write, Read};
+//! ```no_run
 //! use menemen::request::{Request, RequestTypes};
 //!
-//! fn main() {
-//!    let mut request = Request::new("http://postman-echo.com/get", RequestTypes::GET).unwrap();
-//!    let mut response = request.send().unwrap();
-//!    let mut text_buffer = Vec::new();
-//!    response.stream.read_to_end(&mut text_buffer);
-//!    println!("Text: {}", String::from_utf8_lossy(&text_buffer));
-//! }
+//! let mut request = Request::new("http://postman-echo.com/get", RequestTypes::GET).unwrap();
+//! let mut response = request.send().unwrap();
+//! println!("Text: {}", response.text().unwrap());
 //! ```
 //! You can find more examples [here](https://github.com/behemehal/Menemen/tree/main/examples)
 
+/// Basic, Bearer and Digest authentication
+pub mod auth;
+/// Body streams, removes the chunked framing and content encodings of responses and compresses request bodies
+pub mod body;
+/// Client that shares configuration between requests
+pub mod client;
+/// This module contains connectors which open the streams requests are sent over
+pub mod connector;
+/// Cookies and the cookie jar which keeps them between requests
+pub mod cookie;
+/// This module contains host name resolvers
+pub mod dns;
+pub(crate) mod encoding;
 /// Various error types for Menemen
 pub mod error;
+/// `application/x-www-form-urlencoded` bodies
+pub mod form;
+/// HTTP status codes and versions
+pub mod http;
+pub(crate) mod lock;
+/// MIME types used by `Content-Type` and `Accept` headers
+pub mod mime;
+/// `multipart/form-data` bodies
+pub mod multipart;
+/// This module contains proxy configuration
+pub mod proxy;
 /// Request module and http utilities
 pub mod request;
 /// This module contains response structs and utilities enums
 pub mod response;
+pub(crate) mod socks;
 /// This module contains ssl tcp stream
 pub mod transport;
 /// This module contains url utilities
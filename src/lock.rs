@@ -0,0 +1,7 @@
+use std::sync::{Mutex, MutexGuard};
+
+/// Locks the mutex, a panic while another thread held it doesn't make the state unusable
+/// since every value guarded in the crate stays consistent between operations
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|x| x.into_inner())
+}
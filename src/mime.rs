@@ -0,0 +1,181 @@
+use anyhow::Context;
+use std::{fmt, str::FromStr};
+
+/// Checks if the character can be used in a token without quoting
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)
+}
+
+/// MIME type with its parameters like `text/html; charset=utf-8`
+/// ##### [https://developer.mozilla.org/en-US/docs/Web/HTTP/Basics_of_HTTP/MIME_types]
+/// ## Example
+/// ```
+/// use menemen::mime::Mime;
+///
+/// let mime = Mime::parse("Text/HTML; Charset=\"UTF-8\"").unwrap();
+/// assert_eq!(mime.essence(), "text/html");
+/// assert_eq!(mime.get_param("charset"), Some("UTF-8"));
+/// assert_eq!(mime.to_string(), "text/html; charset=UTF-8");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mime {
+    main_type: String,
+    subtype: String,
+    params: Vec<(String, String)>,
+}
+
+impl Mime {
+    /// Create a new [`Mime`] without parameters
+    /// ## Parameters
+    /// * `main_type` - The type like `text`
+    /// * `subtype` - The subtype like `html`
+    pub fn new(main_type: &str, subtype: &str) -> Mime {
+        Mime {
+            main_type: main_type.to_lowercase(),
+            subtype: subtype.to_lowercase(),
+            params: Vec::new(),
+        }
+    }
+
+    /// Parse a MIME type, type, subtype and parameter names are lowercased
+    /// ## Parameters
+    /// * `value` - The MIME type like `text/html; charset=utf-8`
+    /// ## Returns
+    /// [`Mime`] if the value was successfully parsed else [`anyhow::Error`]
+    pub fn parse(value: &str) -> anyhow::Result<Mime> {
+        let (essence, mut rest) = value.split_once(';').unwrap_or((value, ""));
+        let (main_type, subtype) = essence
+            .trim()
+            .split_once('/')
+            .with_context(|| format!("Missing subtype in MIME type '{}'", value))?;
+        let (main_type, subtype) = (main_type.trim(), subtype.trim());
+        if main_type.is_empty()
+            || subtype.is_empty()
+            || !main_type.chars().chain(subtype.chars()).all(is_token_char)
+        {
+            return Err(anyhow::anyhow!("Malformed MIME type '{}'", value));
+        }
+        let mut mime = Mime::new(main_type, subtype);
+        loop {
+            rest = rest.trim_start_matches(|c: char| c == ';' || c.is_whitespace());
+            if rest.is_empty() {
+                return Ok(mime);
+            }
+            let (name, after_name) = rest
+                .split_once('=')
+                .with_context(|| format!("Malformed parameter in MIME type '{}'", value))?;
+            let name = name.trim();
+            if name.is_empty() || !name.chars().all(is_token_char) {
+                return Err(anyhow::anyhow!(
+                    "Malformed parameter in MIME type '{}'",
+                    value
+                ));
+            }
+            let (param_value, after_value) = match after_name.strip_prefix('"') {
+                Some(quoted) => {
+                    let mut param_value = String::new();
+                    let mut chars = quoted.char_indices();
+                    let end = loop {
+                        match chars.next() {
+                            Some((_, '\\')) => {
+                                if let Some((_, c)) = chars.next() {
+                                    param_value.push(c);
+                                }
+                            }
+                            Some((index, '"')) => break index + 1,
+                            Some((_, c)) => param_value.push(c),
+                            None => {
+                                return Err(anyhow::anyhow!(
+                                    "Unterminated quoted parameter in MIME type '{}'",
+                                    value
+                                ))
+                            }
+                        }
+                    };
+                    (param_value, &quoted[end..])
+                }
+                None => {
+                    let (param_value, after_value) =
+                        after_name.split_once(';').unwrap_or((after_name, ""));
+                    (param_value.trim().to_string(), after_value)
+                }
+            };
+            mime.set_param(name, &param_value);
+            rest = after_value;
+        }
+    }
+
+    /// Get the type like `text`
+    pub fn main_type(&self) -> &str {
+        &self.main_type
+    }
+
+    /// Get the subtype like `html`
+    pub fn subtype(&self) -> &str {
+        &self.subtype
+    }
+
+    /// Get the type and subtype without parameters like `text/html`
+    pub fn essence(&self) -> String {
+        format!("{}/{}", self.main_type, self.subtype)
+    }
+
+    /// Get value of the parameter, names are compared case insensitively
+    /// ## Parameters
+    /// * `name` - The name of the parameter
+    pub fn get_param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(x, _)| x.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Set value of the parameter, replacing the existing one
+    /// ## Parameters
+    /// * `name` - The name of the parameter
+    /// * `value` - The value of the parameter, quoted when needed
+    pub fn set_param(&mut self, name: &str, value: &str) {
+        let name = name.to_lowercase();
+        match self.params.iter_mut().find(|(x, _)| *x == name) {
+            Some(param) => param.1 = value.to_string(),
+            None => self.params.push((name, value.to_string())),
+        }
+    }
+
+    /// Get parameters in the order they are set
+    pub fn params(&self) -> &[(String, String)] {
+        &self.params
+    }
+
+    /// Get the `charset` parameter
+    pub fn charset(&self) -> Option<&str> {
+        self.get_param("charset")
+    }
+}
+
+impl fmt::Display for Mime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.main_type, self.subtype)?;
+        for (name, value) in &self.params {
+            if !value.is_empty() && value.chars().all(is_token_char) {
+                write!(f, "; {}={}", name, value)?;
+            } else {
+                write!(
+                    f,
+                    "; {}=\"{}\"",
+                    name,
+                    value.replace('\\', "\\\\").replace('"', "\\\"")
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Mime {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Mime> {
+        Mime::parse(value)
+    }
+}
@@ -0,0 +1,270 @@
+use crate::{encoding, mime::Mime};
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, Cursor, Read},
+    path::Path,
+};
+
+/// Body of a multipart part
+enum PartBody {
+    /// Text value
+    Text(String),
+    /// Any readable source, files and streams are read while sending
+    Reader(Box<dyn Read + Send + Sync>),
+}
+
+/// Part of a [`Multipart`] form, either a text field or a file/stream
+pub struct Part {
+    name: String,
+    filename: Option<String>,
+    content_type: Option<String>,
+    length: Option<u64>,
+    body: PartBody,
+}
+
+impl std::fmt::Debug for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Part")
+            .field("name", &self.name)
+            .field("filename", &self.filename)
+            .field("content_type", &self.content_type)
+            .field("length", &self.length)
+            .finish()
+    }
+}
+
+impl Part {
+    /// Create a text field
+    /// ## Parameters
+    /// * `name` - The name of the field
+    /// * `value` - The value of the field
+    pub fn text(name: &str, value: &str) -> Part {
+        Part {
+            name: name.to_string(),
+            filename: None,
+            content_type: None,
+            length: Some(value.len() as u64),
+            body: PartBody::Text(value.to_string()),
+        }
+    }
+
+    /// Create a file part, the file is read while the request is sent
+    /// ## Parameters
+    /// * `name` - The name of the field
+    /// * `path` - Path of the file, its file name is used as the filename of the part
+    /// ## Returns
+    /// [`Part`] with `application/octet-stream` content type if the file is opened else [`io::Error`]
+    pub fn file<P: AsRef<Path>>(name: &str, path: P) -> io::Result<Part> {
+        let file = File::open(&path)?;
+        let length = file.metadata()?.len();
+        Ok(Part {
+            name: name.to_string(),
+            filename: path
+                .as_ref()
+                .file_name()
+                .map(|x| x.to_string_lossy().to_string()),
+            content_type: Some("application/octet-stream".to_string()),
+            length: Some(length),
+            body: PartBody::Reader(Box::new(file)),
+        })
+    }
+
+    /// Create a stream part, the stream is read while the request is sent
+    /// ## Parameters
+    /// * `name` - The name of the field
+    /// * `reader` - The source of the part, length is unknown unless set with [`Part::set_length`]
+    /// ## Returns
+    /// [`Part`] with `application/octet-stream` content type
+    pub fn reader<R: Read + Send + Sync + 'static>(name: &str, reader: R) -> Part {
+        Part {
+            name: name.to_string(),
+            filename: None,
+            content_type: Some("application/octet-stream".to_string()),
+            length: None,
+            body: PartBody::Reader(Box::new(reader)),
+        }
+    }
+
+    /// Set filename of the part
+    /// ## Parameters
+    /// * `filename` - The filename sent in `Content-Disposition`
+    pub fn set_filename(&mut self, filename: &str) {
+        self.filename = Some(filename.to_string());
+    }
+
+    /// Set content type of the part
+    /// ## Parameters
+    /// * `content_type` - The `Content-Type` of the part
+    pub fn set_content_type(&mut self, content_type: &str) {
+        self.content_type = Some(content_type.to_string());
+    }
+
+    /// Set length of the stream, makes the length of the whole form known so it is sent with `Content-Length`
+    /// ## Parameters
+    /// * `length` - Exact number of bytes the stream will produce
+    pub fn set_length(&mut self, length: u64) {
+        self.length = Some(length);
+    }
+
+    /// Builds the boundary line and headers of the part
+    fn head(&self, boundary: &str) -> String {
+        let mut head = format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"",
+            boundary,
+            escape(&self.name)
+        );
+        if let Some(filename) = &self.filename {
+            head += &format!("; filename=\"{}\"", escape(filename));
+        }
+        head += "\r\n";
+        if let Some(content_type) = &self.content_type {
+            head += &format!("Content-Type: {}\r\n", content_type);
+        }
+        head + "\r\n"
+    }
+}
+
+/// Escapes quotes and line breaks in names as browsers do
+fn escape(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Generates a random boundary which is unlikely to appear in the parts
+fn generate_boundary() -> String {
+    format!("menemen-{}", encoding::random_hex())
+}
+
+/// `multipart/form-data` body, parts are streamed while sending so large files are never buffered
+/// ## Example
+/// ```
+/// use menemen::multipart::{Multipart, Part};
+///
+/// let mut form = Multipart::new();
+/// form.add_text("title", "Menemen");
+/// let mut part = Part::reader("recipe", std::io::Cursor::new("eggs, tomatoes, peppers"));
+/// part.set_filename("recipe.txt");
+/// part.set_content_type("text/plain");
+/// form.add_part(part);
+/// assert_eq!(form.content_type().get_param("boundary"), Some(form.boundary()));
+/// ```
+#[derive(Debug)]
+pub struct Multipart {
+    boundary: String,
+    parts: Vec<Part>,
+}
+
+impl Default for Multipart {
+    fn default() -> Self {
+        Multipart {
+            boundary: generate_boundary(),
+            parts: Vec::new(),
+        }
+    }
+}
+
+impl Multipart {
+    /// Create a new [`Multipart`] with a random boundary
+    pub fn new() -> Multipart {
+        Multipart::default()
+    }
+
+    /// Get boundary separating the parts
+    pub fn boundary(&self) -> &str {
+        &self.boundary
+    }
+
+    /// Get the `Content-Type` of the form with the boundary parameter
+    pub fn content_type(&self) -> Mime {
+        let mut mime = Mime::new("multipart", "form-data");
+        mime.set_param("boundary", &self.boundary);
+        mime
+    }
+
+    /// Add a part to the form
+    /// ## Parameters
+    /// * `part` - The [`Part`] to add
+    pub fn add_part(&mut self, part: Part) {
+        self.parts.push(part);
+    }
+
+    /// Add a text field to the form
+    /// ## Parameters
+    /// * `name` - The name of the field
+    /// * `value` - The value of the field
+    pub fn add_text(&mut self, name: &str, value: &str) {
+        self.add_part(Part::text(name, value));
+    }
+
+    /// Add a file to the form
+    /// ## Parameters
+    /// * `name` - The name of the field
+    /// * `path` - Path of the file
+    /// ## Returns
+    /// [`Ok`] if the file is opened else [`io::Error`]
+    pub fn add_file<P: AsRef<Path>>(&mut self, name: &str, path: P) -> io::Result<()> {
+        self.add_part(Part::file(name, path)?);
+        Ok(())
+    }
+
+    /// Get length of the encoded form
+    /// ## Returns
+    /// Number of bytes if lengths of all parts are known else [`None`]
+    pub fn content_length(&self) -> Option<u64> {
+        self.parts
+            .iter()
+            .try_fold(self.closing().len() as u64, |length, part| {
+                Some(length + part.head(&self.boundary).len() as u64 + part.length? + 2)
+            })
+    }
+
+    /// Builds the closing boundary
+    fn closing(&self) -> String {
+        format!("--{}--\r\n", self.boundary)
+    }
+
+    /// Turns the form into a reader producing the encoded body
+    pub fn into_reader(self) -> MultipartReader {
+        let mut sources: VecDeque<Box<dyn Read + Send + Sync>> = VecDeque::new();
+        let closing = self.closing();
+        for part in self.parts {
+            sources.push_back(Box::new(Cursor::new(part.head(&self.boundary))));
+            sources.push_back(match part.body {
+                PartBody::Text(text) => Box::new(Cursor::new(text)),
+                PartBody::Reader(reader) => reader,
+            });
+            sources.push_back(Box::new(Cursor::new("\r\n")));
+        }
+        sources.push_back(Box::new(Cursor::new(closing)));
+        MultipartReader { sources }
+    }
+}
+
+/// Reader of an encoded [`Multipart`] form, reads the parts one after another
+pub struct MultipartReader {
+    sources: VecDeque<Box<dyn Read + Send + Sync>>,
+}
+
+impl std::fmt::Debug for MultipartReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MultipartReader")
+            .field("remaining_sources", &self.sources.len())
+            .finish()
+    }
+}
+
+impl Read for MultipartReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while let Some(source) = self.sources.front_mut() {
+            let read = source.read(buf)?;
+            if read > 0 || buf.is_empty() {
+                return Ok(read);
+            }
+            self.sources.pop_front();
+        }
+        Ok(0)
+    }
+}
@@ -1,14 +1,19 @@
+use crate::body::{self, Body};
+use crate::error;
+use crate::http::{StatusCode, Version};
+use crate::mime::Mime;
 use crate::request;
-use crate::transport::Transport;
 use anyhow::Context;
+use encoding_rs::Encoding;
+use std::io::{Read, Write};
 
 /// ResponseInfo struct
 #[derive(Clone, Debug, Default)]
 pub struct ResponseInfo {
-    /// The HTTP version of the server
-    pub http_version: String,
-    /// The status code of the response
-    pub status_code: u16,
+    /// The HTTP version of the server [`Version`]
+    pub http_version: Version,
+    /// The status code of the response [`StatusCode`]
+    pub status_code: StatusCode,
     /// The status message of the response
     pub status_message: String,
 }
@@ -20,34 +25,318 @@ impl ResponseInfo {
     /// ## Returns
     /// [`ResponseInfo`] if the answer was successfully parsed else [`anyhow::Error`]
     pub fn parse_response_info(response: &str) -> Result<ResponseInfo, anyhow::Error> {
-        let mut response_info = ResponseInfo {
-            http_version: String::new(),
-            status_code: 0,
-            status_message: String::new(),
-        };
-        let response_info_vec: Vec<&str> = response.split(" ").collect();
+        let response_info_vec: Vec<&str> = response.split(' ').collect();
         if response_info_vec.len() < 2 {
             return Err(anyhow::anyhow!("Failed to parse response info"));
         }
-        response_info.http_version = response_info_vec[0].to_string();
-        response_info.status_code = response_info_vec[1].parse::<u16>().with_context(|| {
-            format!(
-                "Failed to parse status code from response: {}",
-                response_info_vec[1]
-            )
-        })?;
-        response_info.status_message = response_info_vec[2..].join(" ");
-        Ok(response_info)
+        let status_code = response_info_vec[1]
+            .parse::<u16>()
+            .map_err(anyhow::Error::from)
+            .and_then(StatusCode::from_u16)
+            .with_context(|| {
+                format!(
+                    "Failed to parse status code from response: {}",
+                    response_info_vec[1]
+                )
+            })?;
+        Ok(ResponseInfo {
+            http_version: Version::parse(response_info_vec[0])?,
+            status_code,
+            status_message: response_info_vec[2..].join(" "),
+        })
     }
 }
 
-/// [`Response`] struct contains incoming headers ([`Vec<request::Header>`]), [`ResponseInfo`], and stream ([`Transport`]) which implements [`std::io::Read`], [`std::io::Write`] and [`std::io::BufRead`]
-#[allow(missing_debug_implementations)]
+/// [`Response`] struct contains incoming headers ([`Vec<request::Header>`]), [`ResponseInfo`], and stream ([`Body`]) which implements [`std::io::Read`] and [`std::io::BufRead`]
+#[derive(Debug)]
 pub struct Response {
     /// Response info [`ResponseInfo`]
     pub response_info: ResponseInfo,
     /// Response headers [`Vec<request::Header>`]
     pub headers: Vec<request::Header>,
-    /// Incoming body stream
-    pub stream: Transport,
+    /// Incoming body stream, de-chunked and decoded from the content encodings
+    pub stream: Body,
+}
+
+/// Finds the charset declared by `<meta charset>` or `<meta http-equiv>` in the start of an HTML document
+fn sniff_meta_charset(document: &[u8]) -> Option<&'static Encoding> {
+    let start = String::from_utf8_lossy(&document[..document.len().min(1024)]).to_lowercase();
+    start.split("<meta").skip(1).find_map(|tag| {
+        let tag = tag.split('>').next().unwrap_or_default();
+        let (_, value) = tag.split_once("charset=")?;
+        let label = value
+            .trim_start_matches(|c: char| c == '"' || c == '\'' || c.is_whitespace())
+            .split(|c: char| "\"'; />".contains(c) || c.is_whitespace())
+            .next()?;
+        Encoding::for_label(label.as_bytes())
+    })
+}
+
+impl Response {
+    /// Get the parsed `Content-Type` of the response
+    /// ## Returns
+    /// [`Mime`] if the header is sent and valid else [`None`]
+    pub fn content_type(&self) -> Option<Mime> {
+        body::find_header(&self.headers, "Content-Type").and_then(|x| Mime::parse(x).ok())
+    }
+
+    /// Turns 4xx and 5xx responses into [`error::StatusError`] carrying the response
+    /// ## Returns
+    /// The response if its status is not an error else [`error::StatusError`]
+    /// ## Example
+    /// ```no_run
+    /// use menemen::request::{Request, RequestTypes};
+    ///
+    /// let mut request = Request::new("http://behemehal.org/missing", RequestTypes::GET).unwrap();
+    /// match request.send().unwrap().error_for_status() {
+    ///     Ok(response) => println!("{}", response.response_info.status_code),
+    ///     Err(error) => println!("Failed with {}", error.status()),
+    /// }
+    /// ```
+    pub fn error_for_status(self) -> Result<Response, error::StatusError> {
+        let status = self.response_info.status_code;
+        if status.is_client_error() || status.is_server_error() {
+            Err(error::StatusError {
+                response: Box::new(self),
+            })
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// Reads the body to the end and decodes it to text. Charset is taken from the byte order mark,
+    /// then the `Content-Type` charset, then `<meta charset>` of HTML documents, else UTF-8 is used.
+    /// Malformed sequences are replaced with `U+FFFD`
+    /// ## Returns
+    /// Decoded text if the body was read else [`error::RequestErrors`]
+    pub fn text(&mut self) -> Result<String, error::RequestErrors> {
+        let bytes = self.bytes()?;
+        Ok(self.decode_text(&bytes))
+    }
+
+    /// Reads the body to the end, only limited by [`ResponseLimits::max_body_size`]
+    /// ## Returns
+    /// Body bytes if the body was read else [`error::RequestErrors`]
+    pub fn bytes(&mut self) -> Result<Vec<u8>, error::RequestErrors> {
+        let mut bytes = Vec::new();
+        self.stream.read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Reads the body to the end if it is not larger than the limit
+    /// ## Parameters
+    /// * `max_size` - Maximum size of the body in bytes
+    /// ## Returns
+    /// Body bytes if the body was read else [`error::RequestErrors::LimitExceeded`] when body is larger than the limit
+    /// ## Example
+    /// ```no_run
+    /// use menemen::request::{Request, RequestTypes};
+    ///
+    /// let mut request = Request::new("http://behemehal.org", RequestTypes::GET).unwrap();
+    /// let bytes = request.send().unwrap().bytes_with_limit(1024 * 1024).unwrap();
+    /// ```
+    pub fn bytes_with_limit(&mut self, max_size: u64) -> Result<Vec<u8>, error::RequestErrors> {
+        let mut bytes = Vec::new();
+        //One more byte tells if the body is larger than the limit
+        (&mut self.stream)
+            .take(max_size.saturating_add(1))
+            .read_to_end(&mut bytes)?;
+        if bytes.len() as u64 > max_size {
+            return Err(error::RequestErrors::LimitExceeded(
+                error::LimitKind::BodySize,
+            ));
+        }
+        Ok(bytes)
+    }
+
+    /// Copies the body to the writer
+    /// ## Parameters
+    /// * `writer` - The [`std::io::Write`] the body is copied to
+    /// ## Returns
+    /// Number of bytes copied else [`error::RequestErrors`]
+    /// ## Example
+    /// ```no_run
+    /// use menemen::request::{Request, RequestTypes};
+    ///
+    /// let mut request = Request::new("http://behemehal.org", RequestTypes::GET).unwrap();
+    /// let mut file = std::fs::File::create("index.html").unwrap();
+    /// let copied = request.send().unwrap().copy_to(&mut file).unwrap();
+    /// ```
+    pub fn copy_to<W: Write + ?Sized>(
+        &mut self,
+        writer: &mut W,
+    ) -> Result<u64, error::RequestErrors> {
+        Ok(std::io::copy(&mut self.stream, writer)?)
+    }
+
+    /// Turns the response into its body reader, which stops at the end of the body
+    /// ## Returns
+    /// [`Body`] implementing [`std::io::Read`] and [`std::io::BufRead`]
+    pub fn into_reader(self) -> Body {
+        self.stream
+    }
+
+    /// Get the trailer fields sent after a chunked body
+    /// ## Returns
+    /// Trailer [`request::Header`]s once the body is read to the end, empty when the body has no trailers, else [`None`]
+    /// ## Example
+    /// ```no_run
+    /// use menemen::request::{Request, RequestTypes};
+    ///
+    /// let mut request = Request::new("http://behemehal.org/stream", RequestTypes::GET).unwrap();
+    /// let mut response = request.send().unwrap();
+    /// let body = response.bytes().unwrap();
+    /// let checksum = response
+    ///     .trailers()
+    ///     .unwrap()
+    ///     .into_iter()
+    ///     .find(|x| x.name.eq_ignore_ascii_case("X-Checksum"));
+    /// ```
+    pub fn trailers(&self) -> Option<Vec<request::Header>> {
+        self.stream.trailers()
+    }
+
+    /// Reads the body to the end and deserializes it from JSON
+    /// ## Returns
+    /// Deserialized value else [`error::RequestErrors::JsonError`] with the status code and start of the body
+    /// ## Example
+    /// ```no_run
+    /// use menemen::request::{Request, RequestTypes};
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct Echo {
+    ///     url: String,
+    /// }
+    ///
+    /// let mut request = Request::new("http://postman-echo.com/get", RequestTypes::GET).unwrap();
+    /// let echo: Echo = request.send().unwrap().json().unwrap();
+    /// println!("{}", echo.url);
+    /// ```
+    #[cfg(feature = "serde")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+    pub fn json<T: serde::de::DeserializeOwned>(&mut self) -> Result<T, error::RequestErrors> {
+        let bytes = self.bytes()?;
+        serde_json::from_slice(&bytes).map_err(|e| error::RequestErrors::JsonError {
+            status_code: Some(self.response_info.status_code),
+            message: e.to_string(),
+            excerpt: self.decode_text(&bytes).chars().take(256).collect(),
+        })
+    }
+
+    /// Decodes the body bytes with the charset of the response
+    fn decode_text(&self, bytes: &[u8]) -> String {
+        let content_type = self.content_type();
+        let declared = content_type
+            .as_ref()
+            .and_then(|x| x.charset())
+            .and_then(|x| Encoding::for_label(x.as_bytes()));
+        let is_html = content_type
+            .as_ref()
+            .map(|x| x.essence() == "text/html" || x.essence() == "application/xhtml+xml")
+            .unwrap_or(true);
+        let encoding = declared
+            .or_else(|| is_html.then(|| sniff_meta_charset(bytes)).flatten())
+            .unwrap_or(encoding_rs::UTF_8);
+        //Byte order mark overrides the chosen encoding
+        encoding.decode(bytes).0.into_owned()
+    }
+}
+
+/// Limits applied while reading the response, protecting from endless headers and decompression bombs
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ResponseLimits {
+    /// Maximum length of the status line in bytes
+    pub max_status_line_length: usize,
+    /// Maximum bytes of all header lines together, also applied to chunked trailers
+    pub max_header_bytes: usize,
+    /// Maximum number of headers
+    pub max_header_count: usize,
+    /// Maximum size of the decoded body in bytes, [`None`] for no limit
+    pub max_body_size: Option<u64>,
+}
+
+impl Default for ResponseLimits {
+    fn default() -> Self {
+        ResponseLimits {
+            max_status_line_length: 8 * 1024,
+            max_header_bytes: 64 * 1024,
+            max_header_count: 100,
+            max_body_size: None,
+        }
+    }
+}
+
+/// Reads a single `\r\n` terminated line from the stream
+/// ## Parameters
+/// * `stream` - The stream to read from
+/// * `max_length` - Maximum length of the line, `limit` error returned when exceeded
+/// * `limit` - The [`error::LimitKind`] of the line
+fn read_line<R: Read>(
+    stream: &mut R,
+    max_length: usize,
+    limit: error::LimitKind,
+) -> Result<String, error::RequestErrors> {
+    let mut line = String::new();
+    let mut last_char = '\0';
+    loop {
+        let mut buffer = [0; 1];
+        if stream.read(&mut buffer)? == 0 {
+            return Err(error::RequestErrors::ConnectionError(
+                "Connection closed before response head received".to_string(),
+            ));
+        }
+        //Convert byte to char
+        let cchar = char::from(buffer[0]);
+        //If its a line break
+        if last_char == '\r' && cchar == '\n' {
+            line.pop();
+            return Ok(line);
+        }
+        //Line break does not count, trailing '\r' can be the start of it
+        if line.len() > max_length || (line.len() == max_length && cchar != '\r') {
+            return Err(error::RequestErrors::LimitExceeded(limit));
+        }
+        line.push(cchar);
+        last_char = cchar;
+    }
+}
+
+/// Reads the status line and headers of a response, leaving the stream at the start of the body
+pub(crate) fn read_response_head<R: Read>(
+    stream: &mut R,
+    limits: &ResponseLimits,
+) -> Result<(ResponseInfo, Vec<request::Header>), error::RequestErrors> {
+    let status_line = read_line(
+        stream,
+        limits.max_status_line_length,
+        error::LimitKind::StatusLine,
+    )?;
+    let response_info = ResponseInfo::parse_response_info(&status_line)
+        .map_err(|_| error::RequestErrors::ConnectionError("Malformed response".to_string()))?;
+    let mut headers = Vec::new();
+    let mut header_bytes = 0;
+    loop {
+        let line = read_line(
+            stream,
+            limits.max_header_bytes.saturating_sub(header_bytes),
+            error::LimitKind::HeaderBytes,
+        )?;
+        if line.is_empty() {
+            return Ok((response_info, headers));
+        }
+        header_bytes += line.len();
+        if headers.len() == limits.max_header_count {
+            return Err(error::RequestErrors::LimitExceeded(
+                error::LimitKind::HeaderCount,
+            ));
+        }
+        match request::Header::parse(&line) {
+            Ok(header) => headers.push(header),
+            Err(_) => {
+                return Err(error::RequestErrors::ConnectionError(
+                    "Malformed response header".to_string(),
+                ))
+            }
+        }
+    }
 }
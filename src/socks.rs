@@ -0,0 +1,198 @@
+use crate::{
+    dns::Resolve,
+    error,
+    proxy::ProxyCredentials,
+    transport::{self, AddressFamily},
+};
+use std::{
+    io::{Read, Write},
+    net::IpAddr,
+};
+
+/// Returns a connection error with given message
+fn socks_error(message: String) -> error::RequestErrors {
+    error::RequestErrors::ConnectionError(message)
+}
+
+/// Describes SOCKS5 reply codes
+fn socks5_reply_message(code: u8) -> &'static str {
+    match code {
+        0x01 => "general SOCKS server failure",
+        0x02 => "connection not allowed by ruleset",
+        0x03 => "network unreachable",
+        0x04 => "host unreachable",
+        0x05 => "connection refused",
+        0x06 => "TTL expired",
+        0x07 => "command not supported",
+        0x08 => "address type not supported",
+        _ => "unknown error",
+    }
+}
+
+/// Connects to the target through a SOCKS5 proxy
+/// ## Parameters
+/// * `stream` - The connection to the proxy
+/// * `host` - Host name of the target
+/// * `port` - Port number of the target
+/// * `credentials` - Username and password if the proxy requires authentication
+/// * `resolver` - Resolves the host locally, [`None`] sends the host name to the proxy
+/// * `family` - Address families allowed for the locally resolved address
+pub(crate) fn socks5_connect<S: Read + Write>(
+    stream: &mut S,
+    host: &str,
+    port: u16,
+    credentials: Option<&ProxyCredentials>,
+    resolver: Option<&dyn Resolve>,
+    family: AddressFamily,
+) -> Result<(), error::RequestErrors> {
+    //Offer 'no authentication' and 'username/password' if we have credentials
+    let methods: &[u8] = if credentials.is_some() {
+        &[0x00, 0x02]
+    } else {
+        &[0x00]
+    };
+    let mut greeting = vec![0x05, methods.len() as u8];
+    greeting.extend_from_slice(methods);
+    stream.write_all(&greeting)?;
+    stream.flush()?;
+
+    let mut choice = [0; 2];
+    stream.read_exact(&mut choice)?;
+    if choice[0] != 0x05 {
+        return Err(socks_error("Proxy is not a SOCKS5 server".to_string()));
+    }
+    match (choice[1], credentials) {
+        (0x00, _) => {}
+        (0x02, Some(credentials)) => {
+            if credentials.username.len() > 255 || credentials.password.len() > 255 {
+                return Err(socks_error(
+                    "SOCKS5 username and password can't be longer than 255 bytes".to_string(),
+                ));
+            }
+            let mut auth = vec![0x01, credentials.username.len() as u8];
+            auth.extend_from_slice(credentials.username.as_bytes());
+            auth.push(credentials.password.len() as u8);
+            auth.extend_from_slice(credentials.password.as_bytes());
+            stream.write_all(&auth)?;
+            stream.flush()?;
+
+            let mut status = [0; 2];
+            stream.read_exact(&mut status)?;
+            if status[1] != 0x00 {
+                return Err(socks_error("SOCKS5 authentication failed".to_string()));
+            }
+        }
+        _ => {
+            return Err(socks_error(
+                "SOCKS5 proxy has no acceptable authentication method".to_string(),
+            ))
+        }
+    }
+
+    let mut request = vec![0x05, 0x01, 0x00];
+    let address = match (host.parse::<IpAddr>(), resolver) {
+        (Ok(ip), _) => Some(ip),
+        (Err(_), None) => None,
+        (Err(_), Some(resolver)) => {
+            Some(transport::resolve_addresses(host, port, resolver, family)?[0].ip())
+        }
+    };
+    match address {
+        Some(IpAddr::V4(ip)) => {
+            request.push(0x01);
+            request.extend_from_slice(&ip.octets());
+        }
+        Some(IpAddr::V6(ip)) => {
+            request.push(0x04);
+            request.extend_from_slice(&ip.octets());
+        }
+        None => {
+            if host.len() > 255 {
+                return Err(socks_error(
+                    "SOCKS5 host name can't be longer than 255 bytes".to_string(),
+                ));
+            }
+            request.push(0x03);
+            request.push(host.len() as u8);
+            request.extend_from_slice(host.as_bytes());
+        }
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request)?;
+    stream.flush()?;
+
+    let mut reply = [0; 4];
+    stream.read_exact(&mut reply)?;
+    if reply[1] != 0x00 {
+        return Err(socks_error(format!(
+            "SOCKS5 proxy refused connection: {}",
+            socks5_reply_message(reply[1])
+        )));
+    }
+    //Skip bound address and port, we don't need them
+    let bound_len = match reply[3] {
+        0x01 => 4,
+        0x04 => 16,
+        0x03 => {
+            let mut len = [0; 1];
+            stream.read_exact(&mut len)?;
+            len[0] as usize
+        }
+        _ => {
+            return Err(socks_error(
+                "SOCKS5 proxy replied with unknown address type".to_string(),
+            ))
+        }
+    };
+    let mut bound = vec![0; bound_len + 2];
+    stream.read_exact(&mut bound)?;
+    Ok(())
+}
+
+/// Connects to the target through a SOCKS4a proxy, host name is resolved by the proxy
+/// ## Parameters
+/// * `stream` - The connection to the proxy
+/// * `host` - Host name of the target
+/// * `port` - Port number of the target
+/// * `user_id` - User id sent to the proxy
+pub(crate) fn socks4a_connect<S: Read + Write>(
+    stream: &mut S,
+    host: &str,
+    port: u16,
+    user_id: &str,
+) -> Result<(), error::RequestErrors> {
+    let mut request = vec![0x04, 0x01];
+    request.extend_from_slice(&port.to_be_bytes());
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            request.extend_from_slice(&ip.octets());
+            request.extend_from_slice(user_id.as_bytes());
+            request.push(0x00);
+        }
+        Ok(IpAddr::V6(_)) => {
+            return Err(socks_error(
+                "SOCKS4a proxies don't support IPv6 addresses".to_string(),
+            ))
+        }
+        Err(_) => {
+            //0.0.0.x tells the proxy that host name follows the user id
+            request.extend_from_slice(&[0, 0, 0, 1]);
+            request.extend_from_slice(user_id.as_bytes());
+            request.push(0x00);
+            request.extend_from_slice(host.as_bytes());
+            request.push(0x00);
+        }
+    }
+    stream.write_all(&request)?;
+    stream.flush()?;
+
+    let mut reply = [0; 8];
+    stream.read_exact(&mut reply)?;
+    if reply[1] != 0x5A {
+        return Err(socks_error(format!(
+            "SOCKS4a proxy refused connection with code {:#x}",
+            reply[1]
+        )));
+    }
+    Ok(())
+}
@@ -1,17 +1,403 @@
+use crate::{
+    connector::Stream,
+    dns::Resolve,
+    error::{self, TimeoutPhase},
+};
 use bufstream::BufStream;
 use native_tls::TlsStream;
+use socket2::{Domain, Protocol, Socket, TcpKeepalive, Type};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
 use std::{
-    io::{Read, Write},
-    net::TcpStream,
+    io::{self, Read, Write},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream},
+    ops::RangeInclusive,
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
 };
 
 /// This enum is a bridge for the different types of streams that can be used to communicate with the server.
 #[allow(missing_debug_implementations)]
 pub enum Transport {
     /// Ssl stream
-    Ssl(BufStream<TlsStream<TcpStream>>),
+    Ssl(BufStream<TlsStream<TimedStream<TcpStream>>>),
     /// Tcp stream
-    Tcp(BufStream<TcpStream>),
+    Tcp(BufStream<TimedStream<TcpStream>>),
+    /// Unix domain socket stream
+    #[cfg(unix)]
+    Unix(BufStream<TimedStream<UnixStream>>),
+    /// Stream opened by a custom [`crate::connector::Connector`]
+    Custom(Box<dyn Stream>),
+}
+
+/// Timeouts of the request phases, [`None`] means waiting forever
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Timeouts {
+    /// Timeout of establishing the connection, applied per resolved address
+    pub connect: Option<Duration>,
+    /// Timeout of every single write to the connection
+    pub write: Option<Duration>,
+    /// Idle timeout of every single read from the connection
+    pub read: Option<Duration>,
+    /// Deadline of the whole exchange from connecting until the body is read
+    pub total: Option<Duration>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Timeouts {
+            connect: Some(Duration::from_millis(5000)),
+            write: Some(Duration::from_millis(5000)),
+            read: Some(Duration::from_millis(5000)),
+            total: None,
+        }
+    }
+}
+
+/// Sockets that can limit the time spent on blocking reads and writes
+pub trait SocketTimeouts {
+    /// Set timeout of the blocking reads
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+    /// Set timeout of the blocking writes
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+}
+
+impl SocketTimeouts for TcpStream {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        TcpStream::set_read_timeout(self, timeout)
+    }
+
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        TcpStream::set_write_timeout(self, timeout)
+    }
+}
+
+#[cfg(unix)]
+impl SocketTimeouts for UnixStream {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        UnixStream::set_read_timeout(self, timeout)
+    }
+
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        UnixStream::set_write_timeout(self, timeout)
+    }
+}
+
+/// Socket wrapper which applies idle timeouts and the request deadline to every read and write.
+/// Timed out operations fail with [`io::ErrorKind::TimedOut`] carrying the [`TimeoutPhase`]
+#[derive(Debug)]
+pub struct TimedStream<S> {
+    stream: S,
+    read_timeout: Option<Duration>,
+    write_timeout: Option<Duration>,
+    deadline: Option<Instant>,
+}
+
+impl<S: SocketTimeouts> TimedStream<S> {
+    /// Wrap the socket
+    /// ## Parameters
+    /// * `stream` - The connected socket
+    /// * `timeouts` - The [`Timeouts`] of the request
+    /// * `deadline` - The instant the whole exchange should be completed
+    pub fn new(stream: S, timeouts: &Timeouts, deadline: Option<Instant>) -> TimedStream<S> {
+        TimedStream {
+            stream,
+            read_timeout: timeouts.read,
+            write_timeout: timeouts.write,
+            deadline,
+        }
+    }
+
+    /// Get reference of the underlying socket
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Limits timeout of the operation with the time left until the deadline
+    fn limit(
+        &self,
+        timeout: Option<Duration>,
+        phase: TimeoutPhase,
+    ) -> io::Result<(Option<Duration>, TimeoutPhase)> {
+        match self.deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return Err(timeout_error(TimeoutPhase::Deadline));
+                }
+                match timeout {
+                    Some(timeout) if timeout < remaining => Ok((Some(timeout), phase)),
+                    _ => Ok((Some(remaining), TimeoutPhase::Deadline)),
+                }
+            }
+            None => Ok((timeout, phase)),
+        }
+    }
+}
+
+impl<S: SocketTimeouts + Read> Read for TimedStream<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let (timeout, phase) = self.limit(self.read_timeout, TimeoutPhase::Read)?;
+        self.stream.set_read_timeout(timeout)?;
+        self.stream.read(buf).map_err(|e| map_timeout(e, phase))
+    }
+}
+
+impl<S: SocketTimeouts + Write> Write for TimedStream<S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let (timeout, phase) = self.limit(self.write_timeout, TimeoutPhase::Write)?;
+        self.stream.set_write_timeout(timeout)?;
+        self.stream.write(buf).map_err(|e| map_timeout(e, phase))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+/// Builds the error of a timed out operation
+fn timeout_error(phase: TimeoutPhase) -> io::Error {
+    io::Error::new(io::ErrorKind::TimedOut, phase)
+}
+
+/// Tags socket timeout errors with the phase
+fn map_timeout(error: io::Error, phase: TimeoutPhase) -> io::Error {
+    match error.kind() {
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => timeout_error(phase),
+        _ => error,
+    }
+}
+
+/// Options applied to the socket before it connects
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SocketOptions {
+    /// Local address the socket is bound to, only addresses of the same family are connected
+    pub local_address: Option<IpAddr>,
+    /// Local ports to bind, first free port of the range is used
+    pub local_port_range: Option<RangeInclusive<u16>>,
+    /// Set `TCP_NODELAY` to disable Nagle's algorithm
+    pub nodelay: Option<bool>,
+    /// Enable `SO_KEEPALIVE`, probes are sent after the connection is idle this long and repeated with the same interval
+    pub keepalive: Option<Duration>,
+    /// Size of the send buffer `SO_SNDBUF`
+    pub send_buffer_size: Option<usize>,
+    /// Size of the receive buffer `SO_RCVBUF`
+    pub recv_buffer_size: Option<usize>,
+}
+
+impl SocketOptions {
+    /// Creates a socket for the address with the options applied and binds it if requested
+    fn bind(&self, address: &SocketAddr) -> io::Result<Socket> {
+        let socket = Socket::new(
+            Domain::for_address(*address),
+            Type::STREAM,
+            Some(Protocol::TCP),
+        )?;
+        if let Some(nodelay) = self.nodelay {
+            socket.set_nodelay(nodelay)?;
+        }
+        if let Some(keepalive) = self.keepalive {
+            socket.set_tcp_keepalive(
+                &TcpKeepalive::new()
+                    .with_time(keepalive)
+                    .with_interval(keepalive),
+            )?;
+        }
+        if let Some(size) = self.send_buffer_size {
+            socket.set_send_buffer_size(size)?;
+        }
+        if let Some(size) = self.recv_buffer_size {
+            socket.set_recv_buffer_size(size)?;
+        }
+        if self.local_address.is_none() && self.local_port_range.is_none() {
+            return Ok(socket);
+        }
+        let local_address = self.local_address.unwrap_or(match address {
+            SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        });
+        let ports = self.local_port_range.clone().unwrap_or(0..=0);
+        let mut last_error = io::Error::new(io::ErrorKind::AddrInUse, "Local port range is empty");
+        for port in ports {
+            match socket.bind(&SocketAddr::new(local_address, port).into()) {
+                Ok(_) => return Ok(socket),
+                Err(e) => last_error = e,
+            }
+        }
+        Err(last_error)
+    }
+
+    /// Connects to the address with the options applied
+    fn connect(&self, address: &SocketAddr, timeout: Option<Duration>) -> io::Result<TcpStream> {
+        let socket = self.bind(address)?;
+        match timeout {
+            Some(timeout) => socket.connect_timeout(&(*address).into(), timeout)?,
+            None => socket.connect(&(*address).into())?,
+        }
+        Ok(socket.into())
+    }
+}
+
+/// Address families used for connecting
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AddressFamily {
+    /// Race IPv6 and IPv4 addresses with Happy Eyeballs
+    #[default]
+    Any,
+    /// Only connect to IPv4 addresses
+    Ipv4Only,
+    /// Only connect to IPv6 addresses
+    Ipv6Only,
+}
+
+/// Delay before starting the next connection attempt, recommended by RFC 8305
+const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// Filters addresses by family and interleaves them, starting with the family of the first address
+fn sort_addresses(addresses: Vec<SocketAddr>, family: AddressFamily) -> Vec<SocketAddr> {
+    let addresses = addresses
+        .into_iter()
+        .filter(|x| match family {
+            AddressFamily::Any => true,
+            AddressFamily::Ipv4Only => x.is_ipv4(),
+            AddressFamily::Ipv6Only => x.is_ipv6(),
+        })
+        .collect::<Vec<_>>();
+    let Some(first) = addresses.first() else {
+        return addresses;
+    };
+    let first_is_ipv6 = first.is_ipv6();
+    let (preferred, others): (Vec<_>, Vec<_>) = addresses
+        .into_iter()
+        .partition(|x| x.is_ipv6() == first_is_ipv6);
+    let mut sorted = Vec::with_capacity(preferred.len() + others.len());
+    let mut preferred = preferred.into_iter();
+    let mut others = others.into_iter();
+    loop {
+        match (preferred.next(), others.next()) {
+            (None, None) => return sorted,
+            (first, second) => sorted.extend(first.into_iter().chain(second)),
+        }
+    }
+}
+
+/// Resolve host and keep the addresses of given family in the order they should be tried
+/// ## Parameters
+/// * `host` - Host name or ip address
+/// * `port` - Port number
+/// * `resolver` - Resolver of the host name
+/// * `family` - Address families allowed to connect
+/// ## Returns
+/// [`Vec<SocketAddr>`] else [`error::RequestErrors::ConnectionError`] if the host has no address of the family
+pub(crate) fn resolve_addresses(
+    host: &str,
+    port: u16,
+    resolver: &dyn Resolve,
+    family: AddressFamily,
+) -> Result<Vec<SocketAddr>, error::RequestErrors> {
+    let addresses = resolver
+        .resolve(host, port)
+        .map_err(|_| error::RequestErrors::CantResolveUrl)?;
+    if addresses.is_empty() {
+        return Err(error::RequestErrors::CantResolveUrl);
+    }
+    let addresses = sort_addresses(addresses, family);
+    if addresses.is_empty() {
+        let family = match family {
+            AddressFamily::Ipv6Only => "IPv6",
+            _ => "IPv4",
+        };
+        return Err(error::RequestErrors::ConnectionError(format!(
+            "No {} address available for {}",
+            family, host
+        )));
+    }
+    Ok(addresses)
+}
+
+/// Connect timeout of an attempt, limited by the time left until the deadline
+fn attempt_timeout(
+    timeouts: &Timeouts,
+    deadline: Option<Instant>,
+) -> Result<(Option<Duration>, TimeoutPhase), error::RequestErrors> {
+    match deadline {
+        Some(deadline) => {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(error::RequestErrors::ConnectionTimeout(
+                    TimeoutPhase::Deadline,
+                ));
+            }
+            match timeouts.connect {
+                Some(timeout) if timeout < remaining => Ok((Some(timeout), TimeoutPhase::Connect)),
+                _ => Ok((Some(remaining), TimeoutPhase::Deadline)),
+            }
+        }
+        None => Ok((timeouts.connect, TimeoutPhase::Connect)),
+    }
+}
+
+/// Connects to the first reachable address of the host. Attempts are started with a delay between them
+/// and raced against each other (RFC 8305), an attempt failing starts the next one immediately
+/// ## Parameters
+/// * `host` - Host name or ip address
+/// * `port` - Port number
+/// * `resolver` - Resolver of the host name
+/// * `family` - The [`AddressFamily`] of the addresses to try
+/// * `socket_options` - The [`SocketOptions`] applied before connecting
+/// * `timeouts` - The [`Timeouts`] of the request
+/// * `deadline` - The instant the whole exchange should be completed
+pub(crate) fn connect_tcp(
+    host: &str,
+    port: u16,
+    resolver: &dyn Resolve,
+    family: AddressFamily,
+    socket_options: &SocketOptions,
+    timeouts: &Timeouts,
+    deadline: Option<Instant>,
+) -> Result<TimedStream<TcpStream>, error::RequestErrors> {
+    //Bound socket can only reach addresses of its own family
+    let family = match socket_options.local_address {
+        Some(IpAddr::V4(_)) => AddressFamily::Ipv4Only,
+        Some(IpAddr::V6(_)) => AddressFamily::Ipv6Only,
+        None => family,
+    };
+    let mut addresses = resolve_addresses(host, port, resolver, family)?.into_iter();
+    let (sender, receiver) = mpsc::channel();
+    let mut pending = 0;
+    let mut last_error = error::RequestErrors::CantResolveUrl;
+
+    loop {
+        if let Some(address) = addresses.next() {
+            let (timeout, phase) = attempt_timeout(timeouts, deadline)?;
+            let sender = sender.clone();
+            let socket_options = socket_options.clone();
+            pending += 1;
+            thread::spawn(move || {
+                let connection = socket_options.connect(&address, timeout);
+                //Receiver is gone if another attempt won, stream is dropped then
+                let _ = sender.send(connection.map_err(|e| map_timeout(e, phase)));
+            });
+        }
+        if pending == 0 {
+            return Err(last_error);
+        }
+        //Stop waiting after the delay if there are addresses left, failed attempts start the next one at once
+        let result = if addresses.as_slice().is_empty() {
+            receiver.recv().ok()
+        } else {
+            receiver.recv_timeout(CONNECTION_ATTEMPT_DELAY).ok()
+        };
+        match result {
+            Some(Ok(stream)) => return Ok(TimedStream::new(stream, timeouts, deadline)),
+            Some(Err(e)) => {
+                pending -= 1;
+                last_error = e.into();
+            }
+            None => {}
+        }
+    }
 }
 
 impl Write for Transport {
@@ -19,6 +405,9 @@ impl Write for Transport {
         match self {
             Transport::Ssl(socket) => socket.write(buf),
             Transport::Tcp(socket) => socket.write(buf),
+            #[cfg(unix)]
+            Transport::Unix(socket) => socket.write(buf),
+            Transport::Custom(socket) => socket.write(buf),
         }
     }
 
@@ -26,15 +415,42 @@ impl Write for Transport {
         match self {
             Transport::Ssl(socket) => socket.flush(),
             Transport::Tcp(socket) => socket.flush(),
+            #[cfg(unix)]
+            Transport::Unix(socket) => socket.flush(),
+            Transport::Custom(socket) => socket.flush(),
         }
     }
 }
 
+impl Transport {
+    /// Replaces the idle read timeout of the connection
+    /// ## Parameters
+    /// * `timeout` - The new read timeout
+    /// ## Returns
+    /// Previous read timeout, [`None`] if the stream is opened by a custom connector
+    pub(crate) fn replace_read_timeout(
+        &mut self,
+        timeout: Option<Duration>,
+    ) -> Option<Option<Duration>> {
+        let stream_timeout = match self {
+            Transport::Ssl(socket) => &mut socket.get_mut().get_mut().read_timeout,
+            Transport::Tcp(socket) => &mut socket.get_mut().read_timeout,
+            #[cfg(unix)]
+            Transport::Unix(socket) => &mut socket.get_mut().read_timeout,
+            Transport::Custom(_) => return None,
+        };
+        Some(std::mem::replace(stream_timeout, timeout))
+    }
+}
+
 impl Read for Transport {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
         match self {
             Transport::Ssl(socket) => socket.read(buf),
             Transport::Tcp(socket) => socket.read(buf),
+            #[cfg(unix)]
+            Transport::Unix(socket) => socket.read(buf),
+            Transport::Custom(socket) => socket.read(buf),
         }
     }
 }
@@ -44,6 +460,9 @@ impl std::io::BufRead for Transport {
         match self {
             Transport::Ssl(socket) => socket.fill_buf(),
             Transport::Tcp(socket) => socket.fill_buf(),
+            #[cfg(unix)]
+            Transport::Unix(socket) => socket.fill_buf(),
+            Transport::Custom(socket) => socket.fill_buf(),
         }
     }
 
@@ -51,6 +470,9 @@ impl std::io::BufRead for Transport {
         match self {
             Transport::Ssl(socket) => socket.consume(amt),
             Transport::Tcp(socket) => socket.consume(amt),
+            #[cfg(unix)]
+            Transport::Unix(socket) => socket.consume(amt),
+            Transport::Custom(socket) => socket.consume(amt),
         }
     }
 }
@@ -1,4 +1,6 @@
+use crate::encoding;
 use anyhow::{Context, Error};
+use std::path::PathBuf;
 
 /// QueryParam
 #[derive(Clone, Debug, PartialEq)]
@@ -16,12 +18,14 @@ pub struct Url {
     pub is_https: bool,
     /// Host name
     pub host: String,
-    /// Query parameters ([`QueryParam`]) in [`Vec`]
+    /// Query parameters ([`QueryParam`]) in [`Vec`], percent-decoded
     pub query_params: Vec<QueryParam>,
     /// Port number
     pub port: u16,
     /// Paths
     pub paths: Vec<String>,
+    /// Parameters parsed from the url string with their text as written, sent unchanged unless edited
+    raw_query_params: Vec<(QueryParam, String)>,
 }
 
 impl Url {
@@ -85,7 +89,7 @@ impl Url {
                 .iter()
                 .map(|s| {
                     if s.contains("?") {
-                        s.to_string().split("?").collect::<Vec<&str>>()[0].to_string()
+                        s.split('?').collect::<Vec<&str>>()[0].to_string()
                     } else {
                         s.to_string()
                     }
@@ -94,24 +98,21 @@ impl Url {
         } else {
             vec![]
         };
-        let query_params = if paths.len() == 0 {
+        let raw_query_params = if paths.is_empty() {
             vec![]
         } else if new_url.contains("?") {
             new_url = new_url.split("?").collect::<Vec<&str>>()[1].to_string();
             new_url
                 .split("&")
                 .map(|x| {
-                    let param = x.split("=").collect::<Vec<&str>>();
-                    QueryParam {
-                        name: param[0].to_string(),
-                        value: if param.len() == 1 {
-                            String::new()
-                        } else {
-                            param[1].to_string()
-                        },
-                    }
+                    let (name, value) = x.split_once('=').unwrap_or((x, ""));
+                    let param = QueryParam {
+                        name: encoding::form_urldecode(name),
+                        value: encoding::form_urldecode(value),
+                    };
+                    (param, x.to_string())
                 })
-                .collect::<Vec<QueryParam>>()
+                .collect::<Vec<_>>()
         } else {
             Vec::new()
         };
@@ -121,11 +122,13 @@ impl Url {
             host,
             port,
             paths,
-            query_params,
+            query_params: raw_query_params.iter().map(|x| x.0.clone()).collect(),
+            raw_query_params,
         })
     }
 
-    /// Join url parameters according to the url scheme
+    /// Join url parameters according to the url scheme. Parameters of the url string are joined as written,
+    /// names and values of added or edited parameters are percent-encoded
     /// ## Returns
     /// String of joined parameters
     ///
@@ -138,10 +141,27 @@ impl Url {
     /// ```
     pub fn join_query_params(&self) -> String {
         self.query_params
-            .clone()
-            .into_iter()
-            .map(|x| format!("{}={}", x.name, x.value))
+            .iter()
+            .enumerate()
+            .map(|(index, param)| match self.raw_query_params.get(index) {
+                Some((parsed, raw)) if parsed == param => raw.clone(),
+                _ => encoding::form_urlencode_pairs([(param.name.as_str(), param.value.as_str())]),
+            })
             .collect::<Vec<String>>()
             .join("&")
     }
 }
+
+/// Splits `unix://<socket path>:<request path>` url to the socket path and an http url of the request
+/// ## Parameters
+/// * `url` - The url string
+/// ## Returns
+/// Socket path and http url if the url uses `unix` scheme else [`None`]
+pub(crate) fn split_unix_url(url: &str) -> Option<(PathBuf, String)> {
+    let rest = url.strip_prefix("unix://")?;
+    let (socket, path) = match rest.find(":/") {
+        Some(index) => (&rest[..index], &rest[index + 1..]),
+        None => (rest, "/"),
+    };
+    Some((PathBuf::from(socket), format!("http://localhost{}", path)))
+}
@@ -0,0 +1,263 @@
+mod common;
+
+#[cfg(test)]
+mod auth_test {
+    use crate::common::{serve, serve_on, split_request, split_requests};
+    use md5::Md5;
+    use menemen::auth::DigestAuth;
+    use menemen::client::Client;
+    use menemen::http::StatusCode;
+    use menemen::request::{Request, RequestTypes};
+    use sha2::{Digest, Sha256};
+    use std::collections::HashMap;
+    use std::net::TcpListener;
+
+    fn hex<D: Digest>(value: &str) -> String {
+        D::digest(value.as_bytes())
+            .iter()
+            .map(|x| format!("{:02x}", x))
+            .collect()
+    }
+
+    /// Parameters of the `Authorization: Digest` header in the request head
+    fn digest_params(head: &str) -> HashMap<String, String> {
+        let line = head
+            .lines()
+            .find_map(|x| x.strip_prefix("Authorization:Digest "))
+            .unwrap();
+        line.split(", ")
+            .map(|x| {
+                let (key, value) = x.split_once('=').unwrap();
+                (key.to_string(), value.trim_matches('"').to_string())
+            })
+            .collect()
+    }
+
+    const CHALLENGE: &str = "HTTP/1.1 401 Unauthorized\r\n\
+        WWW-Authenticate: Digest realm=\"http-auth@example.org\", qop=\"auth, auth-int\", \
+        algorithm=MD5, nonce=\"7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v\", \
+        opaque=\"FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS\"\r\n\
+        WWW-Authenticate: Digest realm=\"http-auth@example.org\", qop=\"auth\", algorithm=SHA-256, \
+        nonce=\"7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v\", \
+        opaque=\"FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS\"\r\n\
+        Content-Length: 0\r\n\r\n";
+    const OK: &str = "HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n";
+
+    #[test]
+    fn basic_and_bearer_headers() {
+        let mut request = Request::new("http://behemehal.org/", RequestTypes::GET).unwrap();
+        request.set_bearer_auth("mF_9.B5f-4.1JqM");
+        assert_eq!(
+            request.get_header("Authorization").unwrap().value,
+            "Bearer mF_9.B5f-4.1JqM"
+        );
+        request.set_basic_auth("user", "");
+        assert_eq!(
+            request.get_header("Authorization").unwrap().value,
+            "Basic dXNlcjo="
+        );
+        request.set_digest_auth(DigestAuth::new("user", "passwd"));
+        assert!(request.get_header("Authorization").is_none());
+    }
+
+    #[test]
+    fn digest_retries_and_counts_nonce() {
+        let (port, server) = serve(vec![CHALLENGE, OK, OK]);
+        let mut client = Client::new();
+        client.set_digest_auth(DigestAuth::new("Mufasa", "Circle of Life"));
+
+        let url = format!("http://127.0.0.1:{}/dir/index.html?page=1", port);
+        let response = client
+            .request(&url, RequestTypes::GET)
+            .unwrap()
+            .send()
+            .unwrap();
+        assert_eq!(response.response_info.status_code, StatusCode::OK);
+        let response = client
+            .request(&url, RequestTypes::GET)
+            .unwrap()
+            .send()
+            .unwrap();
+        assert_eq!(response.response_info.status_code, StatusCode::OK);
+
+        let requests = split_requests(server.join().unwrap());
+        assert!(!requests[0].0.contains("Authorization"));
+        for (request, nonce_count) in requests[1..].iter().zip(["00000001", "00000002"]) {
+            let params = digest_params(&request.0);
+            assert_eq!(params["username"], "Mufasa");
+            assert_eq!(params["uri"], "/dir/index.html?page=1");
+            assert_eq!(params["algorithm"], "SHA-256");
+            assert_eq!(params["qop"], "auth");
+            assert_eq!(params["nc"], nonce_count);
+            assert_eq!(
+                params["opaque"],
+                "FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS"
+            );
+            let credentials = hex::<Sha256>("Mufasa:http-auth@example.org:Circle of Life");
+            let target = hex::<Sha256>("GET:/dir/index.html?page=1");
+            let expected = hex::<Sha256>(&format!(
+                "{}:{}:{}:{}:auth:{}",
+                credentials, params["nonce"], params["nc"], params["cnonce"], target
+            ));
+            assert_eq!(params["response"], expected);
+        }
+    }
+
+    #[test]
+    fn digest_session_hash_kept_for_nonce() {
+        let (port, server) = serve(vec![
+            "HTTP/1.1 401 Unauthorized\r\n\
+            WWW-Authenticate: Digest realm=\"sess\", qop=\"auth\", algorithm=MD5-sess, nonce=\"n1\"\r\n\
+            Content-Length: 0\r\n\r\n",
+            OK,
+            OK,
+        ]);
+        let mut client = Client::new();
+        client.set_digest_auth(DigestAuth::new("user", "passwd"));
+        let url = format!("http://127.0.0.1:{}/", port);
+        for _ in 0..2 {
+            client
+                .request(&url, RequestTypes::GET)
+                .unwrap()
+                .send()
+                .unwrap();
+        }
+
+        let requests = split_requests(server.join().unwrap());
+        let first = digest_params(&requests[1].0);
+        let second = digest_params(&requests[2].0);
+        assert_eq!(first["algorithm"], "MD5-sess");
+        assert_eq!(second["nc"], "00000002");
+        assert_eq!(first["cnonce"], second["cnonce"]);
+        let credentials = hex::<Md5>(&format!(
+            "{}:n1:{}",
+            hex::<Md5>("user:sess:passwd"),
+            first["cnonce"]
+        ));
+        let target = hex::<Md5>("GET:/");
+        assert_eq!(
+            second["response"],
+            hex::<Md5>(&format!(
+                "{}:n1:00000002:{}:auth:{}",
+                credentials, second["cnonce"], target
+            ))
+        );
+    }
+
+    #[test]
+    fn digest_resends_body() {
+        let (port, server) = serve(vec![
+            "HTTP/1.1 401 Unauthorized\r\n\
+            WWW-Authenticate: Digest realm=\"uploads\", nonce=\"abc\"\r\nContent-Length: 0\r\n\r\n",
+            OK,
+        ]);
+        let mut request = Request::new(
+            &format!("http://127.0.0.1:{}/upload", port),
+            RequestTypes::PUT,
+        )
+        .unwrap();
+        request.set_digest_auth(DigestAuth::new("user", "passwd"));
+        let response = request.send_with_body(&mut "upload".as_bytes()).unwrap();
+        assert_eq!(response.response_info.status_code, StatusCode::OK);
+
+        let requests = split_requests(server.join().unwrap());
+        assert_eq!(requests[0].1, b"upload");
+        assert_eq!(requests[1].1, b"upload");
+        let params = digest_params(&requests[1].0);
+        assert_eq!(params["algorithm"], "MD5");
+        assert!(!params.contains_key("qop"));
+        let credentials = hex::<Md5>("user:uploads:passwd");
+        let target = hex::<Md5>("PUT:/upload");
+        assert_eq!(
+            params["response"],
+            hex::<Md5>(&format!("{}:abc:{}", credentials, target))
+        );
+    }
+
+    #[test]
+    fn digest_gives_up_after_one_retry() {
+        let (port, server) = serve(vec![CHALLENGE, CHALLENGE]);
+        let mut request =
+            Request::new(&format!("http://127.0.0.1:{}/", port), RequestTypes::GET).unwrap();
+        request.set_digest_auth(DigestAuth::new("Mufasa", "wrong"));
+        let response = request.send().unwrap();
+        assert_eq!(response.response_info.status_code, StatusCode::UNAUTHORIZED);
+        assert_eq!(server.join().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn credentials_dropped_on_cross_origin_redirect() {
+        let (other_port, other) = serve(vec![OK]);
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = serve_on(
+            listener,
+            vec![
+                format!(
+                    "HTTP/1.1 302 Found\r\nLocation: http://127.0.0.1:{}/same\r\n\
+                    Content-Length: 0\r\n\r\n",
+                    port
+                ),
+                format!(
+                    "HTTP/1.1 302 Found\r\nLocation: http://127.0.0.1:{}/other\r\n\
+                    Content-Length: 0\r\n\r\n",
+                    other_port
+                ),
+            ],
+        );
+        let mut request = Request::new(
+            &format!("http://127.0.0.1:{}/start", port),
+            RequestTypes::GET,
+        )
+        .unwrap();
+        request.set_bearer_auth("secret");
+        request.set_header("Proxy-Authorization", "Basic c2VjcmV0");
+        let response = request.send().unwrap();
+        assert_eq!(response.response_info.status_code, StatusCode::OK);
+
+        let requests = split_requests(server.join().unwrap());
+        assert!(requests[1].0.contains("Authorization:Bearer secret\r\n"));
+        assert!(requests[1]
+            .0
+            .contains("Proxy-Authorization:Basic c2VjcmV0\r\n"));
+        let (head, _) = split_request(&other.join().unwrap()[0]);
+        assert!(head.starts_with("GET /other "));
+        assert!(!head.contains("Authorization"));
+    }
+
+    #[test]
+    fn digest_answered_on_every_hop() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = serve_on(
+            listener,
+            vec![
+                CHALLENGE.to_string(),
+                format!(
+                    "HTTP/1.1 302 Found\r\nLocation: http://127.0.0.1:{}/next\r\n\
+                    Content-Length: 0\r\n\r\n",
+                    port
+                ),
+                "HTTP/1.1 401 Unauthorized\r\n\
+                WWW-Authenticate: Digest realm=\"next\", nonce=\"fresh\"\r\n\
+                Content-Length: 0\r\n\r\n"
+                    .to_string(),
+                OK.to_string(),
+            ],
+        );
+        let mut request =
+            Request::new(&format!("http://127.0.0.1:{}/", port), RequestTypes::GET).unwrap();
+        request.set_digest_auth(DigestAuth::new("Mufasa", "Circle of Life"));
+        let response = request.send().unwrap();
+        assert_eq!(response.response_info.status_code, StatusCode::OK);
+
+        let requests = split_requests(server.join().unwrap());
+        assert_eq!(
+            digest_params(&requests[2].0)["realm"],
+            "http-auth@example.org"
+        );
+        let params = digest_params(&requests[3].0);
+        assert_eq!(params["realm"], "next");
+        assert_eq!(params["uri"], "/next");
+    }
+}
@@ -0,0 +1,284 @@
+mod common;
+
+#[cfg(test)]
+mod body_test {
+    use crate::common::Canned;
+    use flate2::write::GzEncoder;
+    #[cfg(feature = "deflate")]
+    use flate2::write::{DeflateEncoder, ZlibEncoder};
+    use flate2::Compression;
+    use menemen::error::LimitKind;
+    use menemen::error::RequestErrors;
+    use menemen::request::{Request, RequestTypes};
+    use menemen::response::{Response, ResponseLimits};
+    use std::io::{Read, Write};
+    use std::sync::Arc;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn chunked(data: &[u8], size: usize) -> Vec<u8> {
+        let mut body = Vec::new();
+        for chunk in data.chunks(size) {
+            body.extend_from_slice(format!("{:x};ext=1\r\n", chunk.len()).as_bytes());
+            body.extend_from_slice(chunk);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(b"0\r\nX-Trailer: yes\r\n\r\n");
+        body
+    }
+
+    fn canned_request(head: &str, body: &[u8]) -> Request {
+        let mut raw = head.as_bytes().to_vec();
+        raw.extend_from_slice(body);
+        let mut request = Request::new("http://behemehal.org", RequestTypes::GET).unwrap();
+        request.set_connector(Arc::new(Canned(raw)));
+        request
+    }
+
+    fn response(head: &str, body: &[u8], decompress: bool) -> menemen::response::Response {
+        let mut request = canned_request(head, body);
+        request.set_decompress(decompress);
+        request.send().unwrap()
+    }
+
+    fn limited(head: &str, limits: ResponseLimits) -> Result<Response, RequestErrors> {
+        let mut request = canned_request(head, b"");
+        request.set_limits(limits);
+        request.send()
+    }
+
+    fn read_body(response: &mut menemen::response::Response) -> String {
+        let mut text = String::new();
+        response.stream.read_to_string(&mut text).unwrap();
+        text
+    }
+
+    #[test]
+    fn advertises_encodings() {
+        let mut request = Request::new("http://behemehal.org", RequestTypes::GET).unwrap();
+        assert_eq!(
+            request.get_header("Accept-Encoding").map(|x| x.value),
+            menemen::body::accept_encoding()
+        );
+        request.set_decompress(false);
+        assert!(request.get_header("Accept-Encoding").is_none());
+    }
+
+    #[test]
+    fn content_length_stops_reading() {
+        let mut response = response(
+            "HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n",
+            b"hello world",
+            true,
+        );
+        assert_eq!(read_body(&mut response), "hello");
+    }
+
+    #[test]
+    fn chunked_body() {
+        let mut response = response(
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n",
+            &chunked(b"hello chunked world", 4),
+            true,
+        );
+        assert!(response.trailers().is_none());
+        assert_eq!(read_body(&mut response), "hello chunked world");
+        assert_eq!(response.stream.encoded_bytes_read(), 19);
+        let trailers = response.trailers().unwrap();
+        assert_eq!(trailers.len(), 1);
+        assert_eq!(trailers[0].name, "X-Trailer");
+        assert_eq!(trailers[0].value, "yes");
+    }
+
+    #[test]
+    fn trailers_of_unchunked_body() {
+        let mut response = response(
+            "HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n",
+            b"hello",
+            true,
+        );
+        assert!(response.trailers().is_none());
+        assert_eq!(response.text().unwrap(), "hello");
+        assert!(response.trailers().unwrap().is_empty());
+    }
+
+    #[test]
+    fn malformed_trailer_is_error() {
+        let mut response = response(
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n",
+            b"2\r\nhi\r\n0\r\nnot a field\r\n\r\n",
+            true,
+        );
+        assert!(response.text().is_err());
+        assert!(response.trailers().is_none());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_chunked_body() {
+        let encoded = gzip(b"hello gzip world");
+        let mut response = response(
+            "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\ntransfer-encoding: chunked\r\n\r\n",
+            &chunked(&encoded, 7),
+            true,
+        );
+        assert_eq!(read_body(&mut response), "hello gzip world");
+        assert_eq!(response.stream.encoded_bytes_read(), encoded.len() as u64);
+        assert_eq!(response.trailers().unwrap()[0].name, "X-Trailer");
+    }
+
+    #[cfg(feature = "deflate")]
+    #[test]
+    fn deflate_zlib_and_raw() {
+        let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+        zlib.write_all(b"zlib wrapped").unwrap();
+        let mut raw = DeflateEncoder::new(Vec::new(), Compression::default());
+        raw.write_all(b"raw deflate").unwrap();
+        for (encoded, expected) in [
+            (zlib.finish().unwrap(), "zlib wrapped"),
+            (raw.finish().unwrap(), "raw deflate"),
+        ] {
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Encoding: deflate\r\nContent-Length: {}\r\n\r\n",
+                encoded.len()
+            );
+            assert_eq!(read_body(&mut response(&head, &encoded, true)), expected);
+        }
+    }
+
+    #[cfg(all(feature = "gzip", feature = "deflate"))]
+    #[test]
+    fn stacked_encodings() {
+        let mut deflate = DeflateEncoder::new(Vec::new(), Compression::default());
+        deflate.write_all(b"twice encoded").unwrap();
+        let encoded = gzip(&deflate.finish().unwrap());
+        let mut response = response(
+            "HTTP/1.1 200 OK\r\nContent-Encoding: deflate, gzip\r\n\r\n",
+            &encoded,
+            true,
+        );
+        assert_eq!(read_body(&mut response), "twice encoded");
+        assert_eq!(response.stream.content_encodings(), vec!["deflate", "gzip"]);
+    }
+
+    #[test]
+    fn unknown_or_disabled_encoding_left_as_is() {
+        let encoded = gzip(b"still encoded");
+        for (head, decompress) in [
+            (
+                "HTTP/1.1 200 OK\r\nContent-Encoding: gzip, unknown\r\n\r\n",
+                true,
+            ),
+            ("HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\n\r\n", false),
+        ] {
+            let mut response = response(head, &encoded, decompress);
+            let mut body = Vec::new();
+            response.stream.read_to_end(&mut body).unwrap();
+            assert_eq!(body, encoded);
+        }
+    }
+
+    #[test]
+    fn no_content_has_no_body() {
+        let mut response = response("HTTP/1.1 204 No Content\r\n\r\n", b"ignored", true);
+        assert_eq!(read_body(&mut response), "");
+    }
+
+    #[test]
+    fn truncated_body_is_error() {
+        let mut response = response(
+            "HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\n",
+            b"short",
+            true,
+        );
+        let mut body = Vec::new();
+        assert!(response.stream.read_to_end(&mut body).is_err());
+    }
+
+    #[test]
+    fn head_limits() {
+        let long_status = format!("HTTP/1.1 200 {}\r\n\r\n", "O".repeat(100));
+        let many_headers = format!("HTTP/1.1 200 OK\r\n{}\r\n", "A: b\r\n".repeat(5));
+        let cases = [
+            (
+                long_status.as_str(),
+                ResponseLimits {
+                    max_status_line_length: 64,
+                    ..ResponseLimits::default()
+                },
+                LimitKind::StatusLine,
+            ),
+            (
+                many_headers.as_str(),
+                ResponseLimits {
+                    max_header_bytes: 12,
+                    ..ResponseLimits::default()
+                },
+                LimitKind::HeaderBytes,
+            ),
+            (
+                many_headers.as_str(),
+                ResponseLimits {
+                    max_header_count: 4,
+                    ..ResponseLimits::default()
+                },
+                LimitKind::HeaderCount,
+            ),
+        ];
+        for (head, limits, kind) in cases {
+            match limited(head, limits) {
+                Err(RequestErrors::LimitExceeded(exceeded)) => assert_eq!(exceeded, kind),
+                other => panic!("Expected {:?} limit, got {:?}", kind, other.map(|_| ())),
+            }
+        }
+        //Exactly at the limits is allowed
+        assert!(limited(
+            &many_headers,
+            ResponseLimits {
+                max_header_bytes: 20,
+                max_header_count: 5,
+                ..ResponseLimits::default()
+            }
+        )
+        .is_ok());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn decoded_body_limit() {
+        let encoded = gzip(&[b'a'; 100_000]);
+        let mut request = canned_request(
+            "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\n\r\n",
+            &encoded,
+        );
+        request.set_limits(ResponseLimits {
+            max_body_size: Some(1000),
+            ..ResponseLimits::default()
+        });
+        let mut response = request.send().unwrap();
+        let mut body = Vec::new();
+        let error: RequestErrors = response.stream.read_to_end(&mut body).unwrap_err().into();
+        assert!(matches!(
+            error,
+            RequestErrors::LimitExceeded(LimitKind::BodySize)
+        ));
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn brotli_body() {
+        let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+        encoder.write_all(b"hello brotli").unwrap();
+        let encoded = encoder.into_inner();
+        let mut response = response(
+            "HTTP/1.1 200 OK\r\nContent-Encoding: br\r\n\r\n",
+            &encoded,
+            true,
+        );
+        assert_eq!(read_body(&mut response), "hello brotli");
+    }
+}
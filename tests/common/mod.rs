@@ -0,0 +1,199 @@
+//Helpers shared by the test files, each file uses only some of them
+#![allow(dead_code)]
+
+use menemen::connector::{Connector, Stream};
+use menemen::error::RequestErrors;
+use menemen::url::Url;
+use std::io::{self, BufRead, Read, Write};
+use std::net::TcpListener;
+use std::thread::{self, JoinHandle};
+
+/// Connector answering every request with the same raw response, writes are discarded
+#[derive(Debug)]
+pub struct Canned(pub Vec<u8>);
+
+impl Connector for Canned {
+    fn connect(&self, _url: &Url) -> Result<Box<dyn Stream>, RequestErrors> {
+        Ok(Box::new(CannedStream(io::Cursor::new(self.0.clone()))))
+    }
+}
+
+#[derive(Debug)]
+struct CannedStream(io::Cursor<Vec<u8>>);
+
+impl Read for CannedStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl BufRead for CannedStream {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.0.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.0.consume(amt)
+    }
+}
+
+impl Write for CannedStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Reads from the stream until the read bytes end with `end` or the stream is closed
+fn read_until<S: Read>(stream: &mut S, end: &[u8]) -> Vec<u8> {
+    let mut raw = Vec::new();
+    let mut buffer = [0; 1];
+    while !raw.ends_with(end) {
+        match stream.read(&mut buffer) {
+            Ok(1) => raw.push(buffer[0]),
+            _ => break,
+        }
+    }
+    raw
+}
+
+/// Reads the head of a request
+pub fn read_head<S: Read>(stream: &mut S) -> String {
+    String::from_utf8(read_until(stream, b"\r\n\r\n")).unwrap()
+}
+
+/// Reads a request with its body framed by `Content-Length` or chunked encoding,
+/// stops early if the client closes the connection
+pub fn read_request<S: Read>(stream: &mut S) -> Vec<u8> {
+    let head = read_head(stream);
+    let header = |name: &str| {
+        head.lines().find_map(|x| {
+            let (key, value) = x.split_once(':')?;
+            Some(value.trim().to_string()).filter(|_| key.eq_ignore_ascii_case(name))
+        })
+    };
+    let mut raw = head.clone().into_bytes();
+    if header("Transfer-Encoding").is_some_and(|x| x.contains("chunked")) {
+        loop {
+            let line = read_until(stream, b"\r\n");
+            raw.extend(&line);
+            let line = String::from_utf8_lossy(&line);
+            match usize::from_str_radix(line.trim().split(';').next().unwrap_or_default(), 16) {
+                Ok(0) | Err(_) => break,
+                Ok(size) => {
+                    let mut chunk = vec![0; size + 2];
+                    if stream.read_exact(&mut chunk).is_err() {
+                        return raw;
+                    }
+                    raw.extend(chunk);
+                }
+            }
+        }
+        //Trailer section ends with an empty line
+        loop {
+            let line = read_until(stream, b"\r\n");
+            raw.extend(&line);
+            if line.len() <= 2 {
+                break;
+            }
+        }
+    } else if let Some(length) = header("Content-Length") {
+        let mut body = Vec::new();
+        let _ = stream.take(length.parse().unwrap()).read_to_end(&mut body);
+        raw.extend(body);
+    }
+    raw
+}
+
+/// Splits a raw request to its head and body
+pub fn split_request(raw: &[u8]) -> (String, Vec<u8>) {
+    let split = raw
+        .windows(4)
+        .position(|x| x == b"\r\n\r\n")
+        .map(|x| x + 4)
+        .unwrap_or(raw.len());
+    (
+        String::from_utf8(raw[..split].to_vec()).unwrap(),
+        raw[split..].to_vec(),
+    )
+}
+
+/// Splits raw requests to their heads and bodies
+pub fn split_requests(raws: Vec<Vec<u8>>) -> Vec<(String, Vec<u8>)> {
+    raws.iter().map(|x| split_request(x)).collect()
+}
+
+/// Decodes a chunked body, returns the data and the trailer section
+pub fn dechunk(mut chunks: &[u8]) -> (Vec<u8>, String) {
+    let mut body = Vec::new();
+    loop {
+        let line_end = chunks.windows(2).position(|x| x == b"\r\n").unwrap();
+        let size =
+            usize::from_str_radix(std::str::from_utf8(&chunks[..line_end]).unwrap(), 16).unwrap();
+        if size == 0 {
+            return (
+                body,
+                String::from_utf8(chunks[line_end + 2..].to_vec()).unwrap(),
+            );
+        }
+        body.extend_from_slice(&chunks[line_end + 2..line_end + 2 + size]);
+        chunks = &chunks[line_end + 4 + size..];
+    }
+}
+
+/// Accepts one connection, reads the request and answers with what `answer` builds from it
+/// ## Returns
+/// Port of the server and the handle returning the raw request
+pub fn serve_once_with<F>(answer: F) -> (u16, JoinHandle<Vec<u8>>)
+where
+    F: FnOnce(&[u8]) -> Vec<u8> + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let request = read_request(&mut stream);
+        let _ = stream.write_all(&answer(&request));
+        request
+    });
+    (port, server)
+}
+
+/// Accepts one connection, reads the request and answers with the response
+/// ## Returns
+/// Port of the server and the handle returning the raw request
+pub fn serve_once<R: AsRef<[u8]> + Send + 'static>(response: R) -> (u16, JoinHandle<Vec<u8>>) {
+    serve_once_with(move |_| response.as_ref().to_vec())
+}
+
+/// Answers the connections of the listener in order with the responses
+/// ## Returns
+/// Handle returning the raw requests
+pub fn serve_on<R: AsRef<[u8]> + Send + 'static>(
+    listener: TcpListener,
+    responses: Vec<R>,
+) -> JoinHandle<Vec<Vec<u8>>> {
+    thread::spawn(move || {
+        let mut requests = Vec::new();
+        for response in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            requests.push(read_request(&mut stream));
+            let _ = stream.write_all(response.as_ref());
+        }
+        requests
+    })
+}
+
+/// Answers a connection for every response in order
+/// ## Returns
+/// Port of the server and the handle returning the raw requests
+pub fn serve<R: AsRef<[u8]> + Send + 'static>(
+    responses: Vec<R>,
+) -> (u16, JoinHandle<Vec<Vec<u8>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    (port, serve_on(listener, responses))
+}
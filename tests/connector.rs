@@ -0,0 +1,108 @@
+mod common;
+
+#[cfg(test)]
+mod connector_test {
+    use crate::common::serve_once;
+    use menemen::connector::{Connector, DefaultConnector, Stream};
+    use menemen::error::RequestErrors;
+    use menemen::request::{Request, RequestTypes};
+    use menemen::url::Url;
+    use std::io::{self, BufRead, Read, Write};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    /// In memory stream which answers with canned bytes and records what is written
+    #[derive(Debug)]
+    struct Pipe {
+        response: io::Cursor<Vec<u8>>,
+        written: Arc<Mutex<Vec<u8>>>,
+    }
+
+    impl Read for Pipe {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.response.read(buf)
+        }
+    }
+
+    impl BufRead for Pipe {
+        fn fill_buf(&mut self) -> io::Result<&[u8]> {
+            self.response.fill_buf()
+        }
+
+        fn consume(&mut self, amt: usize) {
+            self.response.consume(amt)
+        }
+    }
+
+    impl Write for Pipe {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.written.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct PipeConnector {
+        written: Arc<Mutex<Vec<u8>>>,
+    }
+
+    impl Connector for PipeConnector {
+        fn connect(&self, _url: &Url) -> Result<Box<dyn Stream>, RequestErrors> {
+            Ok(Box::new(Pipe {
+                response: io::Cursor::new(b"HTTP/1.1 200 OK\r\nX-Pipe: yes\r\n\r\npiped".to_vec()),
+                written: self.written.clone(),
+            }))
+        }
+    }
+
+    /// Counts connections opened by the wrapped default connector
+    #[derive(Debug)]
+    struct CountingConnector {
+        inner: DefaultConnector,
+        count: AtomicUsize,
+    }
+
+    impl Connector for CountingConnector {
+        fn connect(&self, url: &Url) -> Result<Box<dyn Stream>, RequestErrors> {
+            self.count.fetch_add(1, Ordering::SeqCst);
+            self.inner.connect(url)
+        }
+    }
+
+    #[test]
+    fn in_memory_connector() {
+        let connector = Arc::new(PipeConnector::default());
+        let mut request = Request::new("http://example.com/pipe", RequestTypes::GET).unwrap();
+        request.set_connector(connector.clone());
+        let mut response = request.send().unwrap();
+        assert_eq!(response.response_info.status_code, 200);
+        assert!(response.headers.iter().any(|x| x.name == "X-Pipe"));
+        let mut body = String::new();
+        response.stream.read_to_string(&mut body).unwrap();
+        assert_eq!(body, "piped");
+
+        let written = String::from_utf8(connector.written.lock().unwrap().clone()).unwrap();
+        assert!(written.starts_with("GET /pipe HTTP/1.1\r\n"));
+    }
+
+    #[test]
+    fn wrapped_default_connector() {
+        let (port, _server) = serve_once(b"HTTP/1.1 204 No Content\r\n\r\n");
+
+        let mut request =
+            Request::new(&format!("http://127.0.0.1:{}/", port), RequestTypes::GET).unwrap();
+        request.set_env_proxy(false);
+        let connector = Arc::new(CountingConnector {
+            inner: request.default_connector(),
+            count: AtomicUsize::new(0),
+        });
+        request.set_connector(connector.clone());
+        let response = request.send().unwrap();
+        assert_eq!(response.response_info.status_code, 204);
+        assert_eq!(connector.count.load(Ordering::SeqCst), 1);
+    }
+}
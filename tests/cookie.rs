@@ -0,0 +1,296 @@
+mod common;
+
+#[cfg(test)]
+mod cookie_test {
+    use crate::common::{serve_on, split_requests};
+    use menemen::client::Client;
+    use menemen::cookie::{Cookie, CookieJar, SameSite};
+    use menemen::request::RequestTypes;
+    use menemen::url::Url;
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    fn url(url: &str) -> Url {
+        Url::build_from_string(url.to_string()).unwrap()
+    }
+
+    #[test]
+    fn parse_attributes() {
+        let cookie = Cookie::parse(
+            "id=a3fWa; Expires=Thu, 21 Oct 2021 07:28:00 GMT; Path=/docs; SameSite=Lax; HttpOnly",
+            &url("http://behemehal.org/"),
+        )
+        .unwrap();
+        assert_eq!(cookie.name, "id");
+        assert_eq!(cookie.value, "a3fWa");
+        assert_eq!(cookie.domain, "behemehal.org");
+        assert!(cookie.host_only);
+        assert_eq!(cookie.path, "/docs");
+        assert_eq!(cookie.same_site, Some(SameSite::Lax));
+        assert!(cookie.http_only && !cookie.secure);
+        assert_eq!(
+            cookie.expires,
+            Some(UNIX_EPOCH + Duration::from_secs(1634801280))
+        );
+        assert!(cookie.is_expired());
+
+        let cookie = Cookie::parse(
+            "id=1; Max-Age=3600; Expires=Thu, 21 Oct 2021 07:28:00 GMT",
+            &url("http://behemehal.org/"),
+        )
+        .unwrap();
+        assert!(!cookie.is_expired());
+        let cookie = Cookie::parse(
+            "id=1; Max-Age=9223372036854775807",
+            &url("http://behemehal.org/"),
+        )
+        .unwrap();
+        assert!(cookie.expires.unwrap() > SystemTime::now() + Duration::from_secs(1 << 40));
+        assert!(Cookie::parse("id=1", &url("http://behemehal.org/"))
+            .unwrap()
+            .expires
+            .is_none());
+    }
+
+    #[test]
+    fn parse_date_formats() {
+        let expected = Some(UNIX_EPOCH + Duration::from_secs(784111777));
+        for date in [
+            "Sun, 06 Nov 1994 08:49:37 GMT",
+            "Sunday, 06-Nov-94 08:49:37 GMT",
+            "Sun Nov  6 08:49:37 1994",
+        ] {
+            let cookie = Cookie::parse(
+                &format!("id=1; expires={}", date),
+                &url("http://behemehal.org/"),
+            )
+            .unwrap();
+            assert_eq!(cookie.expires, expected, "{}", date);
+        }
+    }
+
+    #[test]
+    fn parse_rejections() {
+        let from = url("http://www.behemehal.org/account/login");
+        assert!(Cookie::parse("no pair", &from).is_err());
+        assert!(Cookie::parse("=value", &from).is_err());
+        assert!(Cookie::parse("id=1; Domain=example.com", &from).is_err());
+        assert!(Cookie::parse("id=1; Domain=org", &from).is_err());
+        assert!(Cookie::parse("id=1; Secure", &from).is_err());
+        assert!(Cookie::parse("id=1; Secure", &url("https://behemehal.org/")).is_ok());
+
+        let cookie = Cookie::parse("id=1; Domain=.Behemehal.org; Path=relative", &from).unwrap();
+        assert_eq!(cookie.domain, "behemehal.org");
+        assert!(!cookie.host_only);
+        assert_eq!(cookie.path, "/account");
+    }
+
+    #[test]
+    fn jar_matching() {
+        let jar = CookieJar::new();
+        let origin = url("https://www.behemehal.org/");
+        jar.set_cookie("host=1", &origin).unwrap();
+        jar.set_cookie("domain=2; Domain=behemehal.org", &origin)
+            .unwrap();
+        jar.set_cookie("docs=3; Path=/docs", &origin).unwrap();
+        jar.set_cookie("secure=4; Secure", &origin).unwrap();
+
+        assert_eq!(
+            jar.cookie_header(&url("https://www.behemehal.org/docs/intro"))
+                .unwrap(),
+            "docs=3; host=1; domain=2; secure=4"
+        );
+        assert_eq!(
+            jar.cookie_header(&url("http://www.behemehal.org/documents"))
+                .unwrap(),
+            "host=1; domain=2"
+        );
+        assert_eq!(
+            jar.cookie_header(&url("http://api.behemehal.org/"))
+                .unwrap(),
+            "domain=2"
+        );
+        assert!(jar.cookie_header(&url("http://example.com/")).is_none());
+
+        jar.set_cookie("host=5", &origin).unwrap();
+        jar.set_cookie("domain=; Domain=behemehal.org; Max-Age=0", &origin)
+            .unwrap();
+        assert_eq!(
+            jar.cookie_header(&url("https://www.behemehal.org/"))
+                .unwrap(),
+            "host=5; secure=4"
+        );
+        assert_eq!(jar.cookies().len(), 3);
+        jar.insert(Cookie {
+            expires: Some(SystemTime::now() - Duration::from_secs(1)),
+            ..jar.cookies()[0].clone()
+        });
+        assert_eq!(jar.cookies().len(), 2);
+        jar.clear();
+        assert!(jar.cookies().is_empty());
+    }
+
+    #[test]
+    fn cookies_follow_redirects() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = serve_on(
+            listener,
+            vec![
+                format!(
+                    "HTTP/1.1 302 Found\r\nSet-Cookie: session=s1; Path=/; HttpOnly\r\n\
+                    Location: http://127.0.0.1:{}/home\r\nContent-Length: 0\r\n\r\n",
+                    port
+                ),
+                "HTTP/1.1 200 OK\r\nSet-Cookie: seen=1\r\nContent-Length: 0\r\n\r\n".to_string(),
+                "HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n".to_string(),
+            ],
+        );
+
+        let jar = Arc::new(CookieJar::new());
+        let mut client = Client::new();
+        client.set_cookie_jar(jar.clone());
+        let login = format!("http://127.0.0.1:{}/login", port);
+        client
+            .request(&login, RequestTypes::GET)
+            .unwrap()
+            .send()
+            .unwrap();
+        let mut request = client
+            .request(
+                &format!("http://127.0.0.1:{}/home", port),
+                RequestTypes::GET,
+            )
+            .unwrap();
+        request.set_header("Cookie", "manual=1");
+        request.send().unwrap();
+
+        let heads = split_requests(server.join().unwrap());
+        assert!(!heads[0].0.contains("Cookie"));
+        assert!(heads[1].0.contains("Cookie:session=s1\r\n"));
+        assert!(heads[2]
+            .0
+            .contains("Cookie:manual=1; session=s1; seen=1\r\n"));
+        assert_eq!(jar.cookies().len(), 2);
+    }
+
+    /// Jar with a persistent, a session and an http only subdomain cookie
+    fn persisted_jar() -> CookieJar {
+        let jar = CookieJar::new();
+        let origin = url("https://www.behemehal.org/account/login");
+        jar.set_cookie(
+            "lang=tr; Expires=Fri, 01 Jan 2100 00:00:00 GMT; Path=/",
+            &origin,
+        )
+        .unwrap();
+        jar.set_cookie("session=s1", &origin).unwrap();
+        jar.set_cookie(
+            "token=t1; Max-Age=3600; Domain=behemehal.org; Secure; HttpOnly; SameSite=Strict",
+            &origin,
+        )
+        .unwrap();
+        jar
+    }
+
+    #[test]
+    fn netscape_round_trip() {
+        let mut file = Vec::new();
+        persisted_jar().save_netscape(&mut file, false).unwrap();
+        let file = String::from_utf8(file).unwrap();
+        let lines = file.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "# Netscape HTTP Cookie File");
+        assert_eq!(
+            lines[1],
+            "www.behemehal.org\tFALSE\t/\tFALSE\t4102444800\tlang\ttr"
+        );
+        assert!(lines[2].starts_with("#HttpOnly_.behemehal.org\tTRUE\t/account\tTRUE\t"));
+
+        let jar = CookieJar::new();
+        jar.load_netscape(file.as_bytes(), false).unwrap();
+        let mut expected = persisted_jar().cookies();
+        expected.remove(1);
+        expected[1].same_site = None;
+        expected[1].expires = jar.cookies()[1].expires;
+        assert_eq!(jar.cookies(), expected);
+
+        let mut file = Vec::new();
+        persisted_jar().save_netscape(&mut file, true).unwrap();
+        let file = String::from_utf8(file).unwrap();
+        assert!(file.contains("www.behemehal.org\tFALSE\t/account\tFALSE\t0\tsession\ts1\n"));
+        let jar = CookieJar::new();
+        jar.load_netscape(file.as_bytes(), false).unwrap();
+        assert_eq!(jar.cookies().len(), 2);
+        jar.load_netscape(file.as_bytes(), true).unwrap();
+        assert_eq!(jar.cookies().len(), 3);
+    }
+
+    #[test]
+    fn netscape_skips_expired_and_rejects_malformed() {
+        let jar = CookieJar::new();
+        jar.load_netscape(
+            "# comment\n\n.behemehal.org\tTRUE\t/\tFALSE\t1\told\tx\r\n\
+            behemehal.org\tFALSE\t/\tFALSE\t4102444800\tempty\t\n"
+                .as_bytes(),
+            true,
+        )
+        .unwrap();
+        let cookies = jar.cookies();
+        assert_eq!(cookies.len(), 1);
+        assert_eq!(cookies[0].name, "empty");
+        assert_eq!(cookies[0].value, "");
+        assert!(jar
+            .load_netscape("behemehal.org\tFALSE\t/\n".as_bytes(), true)
+            .is_err());
+        assert!(jar
+            .load_netscape(
+                "behemehal.org\tFALSE\t/\tFALSE\tsoon\tid\t1\n".as_bytes(),
+                true
+            )
+            .is_err());
+        let far = format!("behemehal.org\tFALSE\t/\tFALSE\t{}\tid\t1\n", u64::MAX);
+        assert!(jar.load_netscape(far.as_bytes(), true).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trip() {
+        let mut file = Vec::new();
+        persisted_jar().save_json(&mut file, true).unwrap();
+        let jar = CookieJar::new();
+        jar.load_json(file.as_slice(), true).unwrap();
+        let mut expected = persisted_jar().cookies();
+        //Expiry times are stored in seconds
+        for (cookie, loaded) in expected.iter_mut().zip(jar.cookies()) {
+            cookie.expires = loaded.expires;
+        }
+        assert_eq!(jar.cookies(), expected);
+        assert_eq!(jar.cookies()[2].same_site, Some(SameSite::Strict));
+
+        let jar = CookieJar::new();
+        jar.load_json(file.as_slice(), false).unwrap();
+        assert_eq!(jar.cookies().len(), 2);
+        assert!(jar.load_json("{}".as_bytes(), true).is_err());
+        assert!(jar
+            .load_json(r#"[{"name": "id"}]"#.as_bytes(), true)
+            .is_err());
+        let far = format!(
+            r#"[{{"name": "id", "value": "1", "domain": "behemehal.org", "expires": {}}}]"#,
+            u64::MAX
+        );
+        assert!(jar.load_json(far.as_bytes(), true).is_err());
+        for expires in [r#""1700000000""#, "-1", "1.5"] {
+            let malformed = format!(
+                r#"[{{"name": "id", "value": "1", "domain": "behemehal.org", "expires": {}}}]"#,
+                expires
+            );
+            assert!(jar.load_json(malformed.as_bytes(), true).is_err());
+        }
+        let session =
+            r#"[{"name": "id", "value": "1", "domain": "behemehal.org", "expires": null}]"#;
+        let jar = CookieJar::new();
+        jar.load_json(session.as_bytes(), true).unwrap();
+        assert!(jar.cookies()[0].expires.is_none());
+    }
+}
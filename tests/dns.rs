@@ -0,0 +1,81 @@
+mod common;
+
+#[cfg(test)]
+mod dns_test {
+    use crate::common::serve_once;
+    use menemen::client::Client;
+    use menemen::dns::{CachingResolver, Resolve, StaticResolver, SystemResolver};
+    use menemen::error::RequestErrors;
+    use menemen::request::RequestTypes;
+    use std::io;
+    use std::net::SocketAddr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[derive(Debug, Default)]
+    struct CountingResolver {
+        calls: AtomicUsize,
+    }
+
+    impl Resolve for CountingResolver {
+        fn resolve(&self, _host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(vec![SocketAddr::from(([127, 0, 0, 1], port))])
+        }
+    }
+
+    #[test]
+    fn static_entries() {
+        let mut resolver = StaticResolver::new(Arc::new(SystemResolver));
+        resolver.add_entry("Staging.Example:8443:10.1.2.3").unwrap();
+        assert_eq!(
+            resolver.resolve("staging.example", 8443).unwrap(),
+            vec![SocketAddr::from(([10, 1, 2, 3], 8443))]
+        );
+        assert!(resolver.add_entry("staging.example:443").is_err());
+        assert!(resolver.add_entry("staging.example:port:10.1.2.3").is_err());
+        assert!(resolver.add_entry("staging.example:443:not-an-ip").is_err());
+    }
+
+    #[test]
+    fn cache_expires() {
+        let counter = Arc::new(CountingResolver::default());
+        let resolver = CachingResolver::new(counter.clone(), Duration::from_millis(100));
+        resolver.resolve("example.com", 80).unwrap();
+        resolver.resolve("EXAMPLE.com", 80).unwrap();
+        resolver.resolve("example.com", 443).unwrap();
+        assert_eq!(counter.calls.load(Ordering::SeqCst), 2);
+        thread::sleep(Duration::from_millis(150));
+        resolver.resolve("example.com", 80).unwrap();
+        assert_eq!(counter.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn pinned_host_through_client() {
+        let (port, _server) = serve_once(b"HTTP/1.1 204 No Content\r\n\r\n");
+
+        let mut resolver = StaticResolver::new(Arc::new(SystemResolver));
+        resolver
+            .add_entry(&format!("staging.menemen.invalid:{}:127.0.0.1", port))
+            .unwrap();
+        let mut client = Client::new();
+        client.set_env_proxy(false);
+        client.set_resolver(Arc::new(resolver));
+
+        let url = format!("http://staging.menemen.invalid:{}/", port);
+        let response = client
+            .request(&url, RequestTypes::GET)
+            .unwrap()
+            .send()
+            .unwrap();
+        assert_eq!(response.response_info.status_code, 204);
+
+        let url = format!("http://other.menemen.invalid:{}/", port);
+        assert!(matches!(
+            client.request(&url, RequestTypes::GET).unwrap().send(),
+            Err(RequestErrors::CantResolveUrl)
+        ));
+    }
+}
@@ -0,0 +1,83 @@
+mod common;
+
+#[cfg(test)]
+mod form_test {
+    use crate::common::serve_once;
+    use menemen::form::Form;
+    use menemen::request::{Request, RequestTypes};
+    use menemen::url::Url;
+
+    #[test]
+    fn encodes_reserved_characters() {
+        let mut form = Form::new();
+        form.add("q", "menemen & çay=1+1");
+        form.add("empty", "");
+        form.add("q", "a_b-c.d*e~f");
+        assert_eq!(
+            form.encode(),
+            "q=menemen+%26+%C3%A7ay%3D1%2B1&empty=&q=a_b-c.d*e%7Ef"
+        );
+    }
+
+    #[test]
+    fn shares_encoding_with_query_params() {
+        let url = Url::build_from_string(
+            "http://behemehal.org/search?q=menemen+%26+%C3%A7ay&x=a=b".to_string(),
+        )
+        .unwrap();
+        assert_eq!(url.query_params[0].value, "menemen & çay");
+        assert_eq!(url.query_params[1].value, "a=b");
+
+        let mut form = Form::new();
+        for param in &url.query_params {
+            form.add(&param.name, &param.value);
+        }
+        assert_eq!(form.encode(), "q=menemen+%26+%C3%A7ay&x=a%3Db");
+        //Query of the url string is sent as written
+        assert_eq!(url.join_query_params(), "q=menemen+%26+%C3%A7ay&x=a=b");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes_struct() {
+        #[derive(serde::Serialize)]
+        struct Login<'a> {
+            user: &'a str,
+            tags: Vec<u8>,
+            code: Option<u32>,
+            remember: bool,
+        }
+
+        let form = Form::from_serialize(&Login {
+            user: "behe mehal",
+            tags: vec![1, 2],
+            code: None,
+            remember: false,
+        })
+        .unwrap();
+        assert_eq!(
+            form.encode(),
+            "user=behe+mehal&tags=1&tags=2&remember=false"
+        );
+        assert!(Form::from_serialize(&"not a struct").is_err());
+    }
+
+    #[test]
+    fn sends_form() {
+        let (port, server) = serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n");
+
+        let mut form = Form::new();
+        form.add("user", "behemehal");
+        form.add("remember", "on");
+        let mut request = Request::new(
+            &format!("http://127.0.0.1:{}/login", port),
+            RequestTypes::POST,
+        )
+        .unwrap();
+        request.send_form(&form).unwrap();
+        let raw = String::from_utf8(server.join().unwrap()).unwrap();
+        assert!(raw.contains("Content-Type:application/x-www-form-urlencoded\r\n"));
+        assert!(raw.contains("Content-Length:26\r\n"));
+        assert!(raw.ends_with("\r\n\r\nuser=behemehal&remember=on"));
+    }
+}
@@ -0,0 +1,74 @@
+mod common;
+
+#[cfg(all(test, feature = "serde"))]
+mod json_test {
+    use crate::common::{serve_once_with, split_request};
+    use menemen::error::RequestErrors;
+    use menemen::http::StatusCode;
+    use menemen::request::{Request, RequestTypes};
+    use serde::{Deserialize, Serialize};
+    use std::io::Write;
+    use std::thread;
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Event {
+        name: String,
+        count: u32,
+    }
+
+    /// Accepts one connection, answers with the status and the request body sent back chunked
+    fn serve_echo(status: &'static str) -> (u16, thread::JoinHandle<Vec<u8>>) {
+        serve_once_with(move |request| {
+            let (_, body) = split_request(request);
+            let mut answer = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nTransfer-Encoding: chunked\r\n\r\n",
+                status
+            )
+            .into_bytes();
+            for chunk in body.chunks(5) {
+                write!(answer, "{:x}\r\n", chunk.len()).unwrap();
+                answer.extend_from_slice(chunk);
+                answer.extend_from_slice(b"\r\n");
+            }
+            answer.extend_from_slice(b"0\r\n\r\n");
+            answer
+        })
+    }
+
+    #[test]
+    fn round_trip() {
+        let (port, server) = serve_echo("200 OK");
+        let event = Event {
+            name: "menemen".to_string(),
+            count: 3,
+        };
+        let mut request =
+            Request::new(&format!("http://127.0.0.1:{}/", port), RequestTypes::POST).unwrap();
+        let mut response = request.json(&event).unwrap();
+        assert_eq!(response.json::<Event>().unwrap(), event);
+        let (head, _) = split_request(&server.join().unwrap());
+        assert!(head.contains("Content-Type:application/json\r\n"));
+    }
+
+    #[test]
+    fn error_has_status_and_excerpt() {
+        let (port, _server) = serve_echo("502 Bad Gateway");
+        let mut request =
+            Request::new(&format!("http://127.0.0.1:{}/", port), RequestTypes::POST).unwrap();
+        let mut response = request
+            .json(&serde_json::json!({ "name": "menemen" }))
+            .unwrap();
+        match response.json::<Event>() {
+            Err(RequestErrors::JsonError {
+                status_code,
+                message,
+                excerpt,
+            }) => {
+                assert_eq!(status_code, Some(StatusCode::BAD_GATEWAY));
+                assert!(message.contains("count"));
+                assert_eq!(excerpt, "{\"name\":\"menemen\"}");
+            }
+            other => panic!("Expected json error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod mime_test {
+    use menemen::mime::Mime;
+    use menemen::request::ContentTypes;
+
+    #[test]
+    fn parses_params() {
+        let mime: Mime = "multipart/form-data ; boundary=\"a;b \\\"c\\\"\"; Charset=utf-8"
+            .parse()
+            .unwrap();
+        assert_eq!(mime.main_type(), "multipart");
+        assert_eq!(mime.subtype(), "form-data");
+        assert_eq!(mime.get_param("boundary"), Some("a;b \"c\""));
+        assert_eq!(mime.charset(), Some("utf-8"));
+        assert_eq!(
+            mime.to_string(),
+            "multipart/form-data; boundary=\"a;b \\\"c\\\"\"; charset=utf-8"
+        );
+        assert_eq!(Mime::parse(&mime.to_string()).unwrap(), mime);
+    }
+
+    #[test]
+    fn rejects_malformed() {
+        for value in [
+            "text",
+            "/html",
+            "text/",
+            "te xt/html",
+            "text/html; charset",
+            "text/html; a=\"open",
+        ] {
+            assert!(Mime::parse(value).is_err(), "{} should not parse", value);
+        }
+    }
+
+    #[test]
+    fn content_types_from_strings() {
+        assert_eq!(
+            "text/plain".parse::<ContentTypes>().unwrap(),
+            ContentTypes::Text
+        );
+        let mut mime = Mime::new("text", "plain");
+        mime.set_param("charset", "iso-8859-9");
+        assert_eq!(
+            ContentTypes::parse("text/plain; charset=iso-8859-9").unwrap(),
+            ContentTypes::Mime(mime)
+        );
+        assert_eq!(
+            ContentTypes::parse("application/vnd.api+json")
+                .unwrap()
+                .get_type(),
+            "application/vnd.api+json"
+        );
+    }
+}
@@ -0,0 +1,107 @@
+mod common;
+
+#[cfg(test)]
+mod multipart_test {
+    use crate::common::{serve_once, split_request};
+    use menemen::error::RequestErrors;
+    use menemen::multipart::{Multipart, Part};
+    use menemen::request::{Request, RequestTypes};
+    use std::io::{Cursor, Read};
+
+    const OK: &[u8] = b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n";
+
+    fn form() -> Multipart {
+        let mut form = Multipart::new();
+        form.add_text("title", "Men\"emen");
+        let mut part = Part::reader("recipe", Cursor::new("eggs\r\ntomatoes"));
+        part.set_filename("recipe.txt");
+        part.set_content_type("text/plain");
+        part.set_length(14);
+        form.add_part(part);
+        form
+    }
+
+    #[test]
+    fn encodes_parts() {
+        let form = form();
+        let boundary = form.boundary().to_string();
+        let length = form.content_length();
+        let mut encoded = String::new();
+        form.into_reader().read_to_string(&mut encoded).unwrap();
+        assert_eq!(
+            encoded,
+            format!(
+                "--{b}\r\nContent-Disposition: form-data; name=\"title\"\r\n\r\nMen\"emen\r\n\
+                --{b}\r\nContent-Disposition: form-data; name=\"recipe\"; filename=\"recipe.txt\"\r\n\
+                Content-Type: text/plain\r\n\r\neggs\r\ntomatoes\r\n--{b}--\r\n",
+                b = boundary
+            )
+        );
+        assert_eq!(length, Some(encoded.len() as u64));
+        assert_ne!(Multipart::new().boundary(), boundary);
+    }
+
+    #[test]
+    fn sends_file_with_length() {
+        let path = std::env::temp_dir().join("menemen_multipart_test.txt");
+        std::fs::write(&path, "file content").unwrap();
+        let mut form = form();
+        form.add_file("document", &path).unwrap();
+        let content_type = form.content_type();
+        let (port, server) = serve_once(OK);
+
+        let mut request = Request::new(
+            &format!("http://127.0.0.1:{}/upload", port),
+            RequestTypes::POST,
+        )
+        .unwrap();
+        let response = request.send_multipart(form).unwrap();
+        assert_eq!(response.response_info.status_code, 200);
+        let (head, body) = split_request(&server.join().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        let body = String::from_utf8(body).unwrap();
+        let lines = head.split("\r\n").collect::<Vec<_>>();
+        assert!(lines.contains(&format!("Content-Type:{}", content_type).as_str()));
+        assert!(lines.contains(&format!("Content-Length:{}", body.len()).as_str()));
+        assert!(body.contains(
+            "name=\"document\"; filename=\"menemen_multipart_test.txt\"\r\n\
+            Content-Type: application/octet-stream\r\n\r\nfile content\r\n"
+        ));
+    }
+
+    #[test]
+    fn unknown_length_is_chunked() {
+        let mut form = Multipart::new();
+        form.add_part(Part::reader("stream", Cursor::new(vec![b'x'; 20_000])));
+        let (port, server) = serve_once(OK);
+
+        let mut request = Request::new(
+            &format!("http://127.0.0.1:{}/upload", port),
+            RequestTypes::POST,
+        )
+        .unwrap();
+        request.send_multipart(form).unwrap();
+        let (head, _) = split_request(&server.join().unwrap());
+        assert!(head.split("\r\n").any(|x| x == "Transfer-Encoding:chunked"));
+        assert!(!head.contains("Content-Length"));
+    }
+
+    #[test]
+    fn longer_stream_than_length() {
+        let (port, _server) = serve_once(OK);
+        let mut form = Multipart::new();
+        let mut part = Part::reader("stream", Cursor::new("longer than told"));
+        part.set_length(6);
+        form.add_part(part);
+
+        let mut request = Request::new(
+            &format!("http://127.0.0.1:{}/upload", port),
+            RequestTypes::POST,
+        )
+        .unwrap();
+        assert!(matches!(
+            request.send_multipart(form),
+            Err(RequestErrors::ConnectionError(_))
+        ));
+    }
+}
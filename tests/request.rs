@@ -1,6 +1,23 @@
+mod common;
+
 #[cfg(test)]
 mod request_test {
-    use menemen::request::{Request, RequestTypes};
+    use crate::common::{dechunk, read_head, serve, serve_once, split_request};
+    use menemen::http::StatusCode;
+    use menemen::request::{ContentTypes, Header, Request, RequestTypes};
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    fn assert_sync<T: Sync>() {}
+
+    #[test]
+    fn request_and_response_are_sync() {
+        assert_sync::<Request>();
+        assert_sync::<menemen::response::Response>();
+    }
 
     #[test]
     fn get_set_header_test() {
@@ -16,4 +33,186 @@ mod request_test {
         let mut request = Request::new("https://behemehal.org/test", RequestTypes::GET).unwrap();
         assert!(request.set_timeout(100).is_none());
     }
+
+    #[test]
+    fn content_type_only_with_body() {
+        let (port, server) = serve(vec![b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n"; 2]);
+        let url = format!("http://127.0.0.1:{}/", port);
+
+        let mut request = Request::new(&url, RequestTypes::GET).unwrap();
+        request.set_accept(ContentTypes::JSON);
+        request.send().unwrap();
+        let mut request = Request::new(&url, RequestTypes::POST).unwrap();
+        request.content_type = ContentTypes::parse("text/csv; charset=utf-8").unwrap();
+        request.send_with_body(&mut "a,b".as_bytes()).unwrap();
+
+        let heads = server
+            .join()
+            .unwrap()
+            .iter()
+            .map(|x| split_request(x).0)
+            .collect::<Vec<_>>();
+        assert!(!heads[0].contains("Content-Type"));
+        assert!(heads[0].contains("Accept:application/json\r\n"));
+        assert!(heads[1].contains("Content-Type:text/csv; charset=utf-8\r\n"));
+        assert!(heads[1].contains("Accept:*/*\r\n"));
+    }
+
+    /// Accepts one connection, answers the head with `answer`, then reads a body of `body_length` bytes
+    /// and answers it with `200 OK`, returns the head and the body received
+    fn serve_expect(
+        answer: &'static [u8],
+        body_length: usize,
+    ) -> (u16, thread::JoinHandle<(String, Vec<u8>)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let head = read_head(&mut stream);
+            stream.write_all(answer).unwrap();
+            let mut body = vec![0; body_length];
+            stream.read_exact(&mut body).unwrap();
+            if body_length > 0 {
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\ndone")
+                    .unwrap();
+            }
+            (head, body)
+        });
+        (port, server)
+    }
+
+    #[test]
+    fn interim_responses_skipped() {
+        let (port, server) = serve_expect(
+            b"HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 103 Early Hints\r\nLink: </style.css>; rel=preload\r\n\r\n\
+            HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok",
+            0,
+        );
+        let hints = Arc::new(Mutex::new(Vec::new()));
+        let received = hints.clone();
+        let mut request =
+            Request::new(&format!("http://127.0.0.1:{}/", port), RequestTypes::GET).unwrap();
+        request.set_interim_callback(move |info, headers| {
+            received
+                .lock()
+                .unwrap()
+                .push((info.status_code, headers.to_vec()));
+        });
+        let mut response = request.send().unwrap();
+        assert_eq!(response.response_info.status_code, StatusCode::OK);
+        assert_eq!(response.text().unwrap(), "ok");
+        server.join().unwrap();
+
+        let hints = hints.lock().unwrap();
+        assert_eq!(hints.len(), 2);
+        assert_eq!(hints[0].0, StatusCode::CONTINUE);
+        assert_eq!(hints[1].0, StatusCode::EARLY_HINTS);
+        assert_eq!(hints[1].1[0].value.trim(), "</style.css>; rel=preload");
+    }
+
+    #[test]
+    fn expect_continue_waits_for_go_ahead() {
+        let (port, server) = serve_expect(b"HTTP/1.1 100 Continue\r\n\r\n", 6);
+        let mut request =
+            Request::new(&format!("http://127.0.0.1:{}/", port), RequestTypes::PUT).unwrap();
+        request.set_expect_continue(Some(Duration::from_secs(5)));
+        let mut response = request.send_with_body(&mut "upload".as_bytes()).unwrap();
+        assert_eq!(response.text().unwrap(), "done");
+        let (head, body) = server.join().unwrap();
+        assert!(head.contains("Expect:100-continue\r\n"));
+        assert_eq!(body, b"upload");
+    }
+
+    #[test]
+    fn expect_continue_rejected() {
+        let (port, server) = serve_expect(
+            b"HTTP/1.1 417 Expectation Failed\r\nContent-Length: 0\r\n\r\n",
+            0,
+        );
+        let mut request =
+            Request::new(&format!("http://127.0.0.1:{}/", port), RequestTypes::PUT).unwrap();
+        request.set_expect_continue(Some(Duration::from_secs(5)));
+        let response = request.send_with_body(&mut "upload".as_bytes()).unwrap();
+        assert_eq!(
+            response.response_info.status_code,
+            StatusCode::EXPECTATION_FAILED
+        );
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn expect_continue_ignored_by_server() {
+        let (port, server) = serve_expect(b"", 6);
+        let mut request =
+            Request::new(&format!("http://127.0.0.1:{}/", port), RequestTypes::PUT).unwrap();
+        request.set_expect_continue(Some(Duration::from_millis(200)));
+        let mut response = request.send_with_body(&mut "upload".as_bytes()).unwrap();
+        assert_eq!(response.text().unwrap(), "done");
+        assert_eq!(server.join().unwrap().1, b"upload");
+    }
+
+    /// Accepts one connection, reads a chunked upload and returns its head, de-chunked body and trailer section
+    fn serve_chunked_upload() -> (u16, thread::JoinHandle<(String, Vec<u8>, String)>) {
+        let (port, server) = serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n");
+        let server = thread::spawn(move || {
+            let (head, body) = split_request(&server.join().unwrap());
+            let (body, trailers) = dechunk(&body);
+            (head, body, trailers)
+        });
+        (port, server)
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_compressed_upload() {
+        use menemen::request::BodyCompression;
+
+        let (port, server) = serve_chunked_upload();
+        let mut request = Request::new(
+            &format!("http://127.0.0.1:{}/ingest", port),
+            RequestTypes::POST,
+        )
+        .unwrap();
+        request.set_body_compression(Some(BodyCompression::Gzip));
+        let payload = "{\"event\":\"batch\"}\n".repeat(1000);
+        let response = request.send_with_body(&mut payload.as_bytes()).unwrap();
+        assert_eq!(response.response_info.status_code, 200);
+
+        let (head, body, _) = server.join().unwrap();
+        assert!(head.contains("Content-Encoding:gzip\r\n"));
+        assert!(head.contains("Transfer-Encoding:chunked\r\n"));
+        assert!(!head.to_lowercase().contains("content-length"));
+        assert!(body.len() < payload.len());
+        let mut decoded = String::new();
+        flate2::read::GzDecoder::new(body.as_slice())
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, payload);
+    }
+
+    #[test]
+    fn trailers_after_chunked_upload() {
+        let (port, server) = serve_chunked_upload();
+        let mut request = Request::new(
+            &format!("http://127.0.0.1:{}/upload", port),
+            RequestTypes::PUT,
+        )
+        .unwrap();
+        request.set_header("Trailer", "X-Checksum");
+        request.set_trailers(|| {
+            vec![Header {
+                name: "X-Checksum".to_string(),
+                value: "abc123".to_string(),
+            }]
+        });
+        let response = request.send_with_body(&mut "upload".as_bytes()).unwrap();
+        assert_eq!(response.response_info.status_code, 200);
+
+        let (head, body, trailers) = server.join().unwrap();
+        assert!(head.contains("Transfer-Encoding:chunked\r\n"));
+        assert!(!head.to_lowercase().contains("content-length"));
+        assert_eq!(body, b"upload");
+        assert_eq!(trailers, "X-Checksum:abc123\r\n\r\n");
+    }
 }
@@ -1,12 +1,141 @@
+mod common;
+
 #[cfg(test)]
 mod url_test {
+    use menemen::http::{StatusCode, Version};
     use menemen::response::ResponseInfo;
 
     #[test]
     fn url_test() {
         let header = ResponseInfo::parse_response_info("HTTP/1.1 200 OK").unwrap();
         assert_eq!(header.status_code, 200);
+        assert_eq!(header.status_code, StatusCode::OK);
         assert_eq!(header.status_message, "OK");
-        assert_eq!(header.http_version, "HTTP/1.1");
+        assert_eq!(header.http_version, Version::Http11);
+    }
+
+    #[test]
+    fn status_classes() {
+        let header = ResponseInfo::parse_response_info("HTTP/1.0 599 Custom Error").unwrap();
+        assert_eq!(header.http_version, Version::Http10);
+        assert!(header.status_code.is_server_error());
+        assert_eq!(header.status_code.canonical_reason(), None);
+        assert_eq!(header.status_code.to_string(), "599");
+        assert!(StatusCode::PERMANENT_REDIRECT.is_redirection());
+        assert!(StatusCode::EARLY_HINTS.is_informational());
+        assert!(!StatusCode::NOT_MODIFIED.is_success());
+        assert!(StatusCode::from_u16(99).is_err());
+        assert_eq!(
+            StatusCode::NON_AUTHORITATIVE_INFORMATION.canonical_reason(),
+            Some("Non-Authoritative Information")
+        );
+        assert!(ResponseInfo::parse_response_info("HTTP/1.1 abc OK").is_err());
+        assert!(ResponseInfo::parse_response_info("SPDY/1 200 OK").is_err());
+    }
+}
+
+#[cfg(test)]
+mod text_test {
+    use crate::common::Canned;
+    use menemen::error::{LimitKind, RequestErrors};
+    use menemen::http::StatusCode;
+    use menemen::request::{Request, RequestTypes};
+    use menemen::response::Response;
+    use std::io::Read;
+    use std::sync::Arc;
+
+    fn canned_response(content_type: Option<&str>, body: &[u8]) -> Response {
+        let mut raw = b"HTTP/1.1 200 OK\r\n".to_vec();
+        if let Some(content_type) = content_type {
+            raw.extend_from_slice(format!("content-type: {}\r\n", content_type).as_bytes());
+        }
+        raw.extend_from_slice(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes());
+        raw.extend_from_slice(body);
+        let mut request = Request::new("http://behemehal.org", RequestTypes::GET).unwrap();
+        request.set_connector(Arc::new(Canned(raw)));
+        request.send().unwrap()
+    }
+
+    #[test]
+    fn parses_content_type() {
+        let response = canned_response(Some("Text/HTML; charset=ISO-8859-9"), b"");
+        let mime = response.content_type().unwrap();
+        assert_eq!(mime.essence(), "text/html");
+        assert_eq!(mime.charset(), Some("ISO-8859-9"));
+        assert!(canned_response(None, b"").content_type().is_none());
+    }
+
+    #[test]
+    fn declared_charset() {
+        let mut response =
+            canned_response(Some("text/plain; charset=iso-8859-9"), b"Men\xfcmen \xfeey");
+        assert_eq!(response.text().unwrap(), "Menümen şey");
+    }
+
+    #[test]
+    fn meta_charset_sniffing() {
+        let mut body = b"<html><head><META http-equiv=\"Content-Type\" content=\"text/html; charset=Shift_JIS\"></head>".to_vec();
+        body.extend_from_slice(b"\x93\xfa\x96\x7b");
+        let mut response = canned_response(Some("text/html"), &body);
+        assert!(response.text().unwrap().ends_with("日本"));
+
+        let mut response = canned_response(None, b"<meta charset='windows-1254'>\xe7ay");
+        assert!(response.text().unwrap().ends_with("çay"));
+    }
+
+    #[test]
+    fn bom_and_default() {
+        let mut response = canned_response(
+            Some("text/plain; charset=iso-8859-1"),
+            b"\xff\xfeh\x00i\x00",
+        );
+        assert_eq!(response.text().unwrap(), "hi");
+
+        let mut response = canned_response(Some("text/plain"), "menemen ✓".as_bytes());
+        assert_eq!(response.text().unwrap(), "menemen ✓");
+    }
+
+    #[test]
+    fn error_for_status_keeps_response() {
+        let mut request = Request::new("http://behemehal.org", RequestTypes::GET).unwrap();
+        request.set_connector(Arc::new(Canned(
+            b"HTTP/1.1 404 Not Found\r\nContent-Length: 7\r\n\r\nmissing".to_vec(),
+        )));
+        let mut error = request.send().unwrap().error_for_status().unwrap_err();
+        assert_eq!(error.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            error.to_string(),
+            "HTTP status client error (404 Not Found)"
+        );
+        assert_eq!(error.response.text().unwrap(), "missing");
+        assert!(canned_response(None, b"").error_for_status().is_ok());
+    }
+
+    #[test]
+    fn bytes_with_limit() {
+        assert_eq!(
+            canned_response(None, b"menemen")
+                .bytes_with_limit(7)
+                .unwrap(),
+            b"menemen"
+        );
+        assert!(matches!(
+            canned_response(None, b"menemen").bytes_with_limit(6),
+            Err(RequestErrors::LimitExceeded(LimitKind::BodySize))
+        ));
+    }
+
+    #[test]
+    fn copy_and_reader_stop_at_body_end() {
+        let mut copied = Vec::new();
+        let mut response = canned_response(None, b"menemen");
+        assert_eq!(response.copy_to(&mut copied).unwrap(), 7);
+        assert_eq!(copied, b"menemen");
+
+        let mut reader = canned_response(None, b"menemen").into_reader();
+        let mut text = String::new();
+        reader.read_to_string(&mut text).unwrap();
+        assert_eq!(text, "menemen");
+        assert_eq!(reader.read(&mut [0; 8]).unwrap(), 0);
     }
 }
@@ -0,0 +1,256 @@
+mod common;
+
+#[cfg(test)]
+mod transport_test {
+    use crate::common::{read_head, read_request};
+    use menemen::dns::Resolve;
+    use menemen::error::{RequestErrors, TimeoutPhase};
+    use menemen::request::{Request, RequestTypes};
+    use menemen::transport::{AddressFamily, SocketOptions, Timeouts};
+    use socket2::{Domain, Socket, Type};
+    use std::io::{self, Read, Write};
+    use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream};
+    use std::sync::{mpsc, Arc};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    /// Resolves every host to the given addresses
+    #[derive(Debug)]
+    struct FixedResolver(Vec<IpAddr>);
+
+    impl Resolve for FixedResolver {
+        fn resolve(&self, _host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+            Ok(self.0.iter().map(|ip| SocketAddr::new(*ip, port)).collect())
+        }
+    }
+
+    /// Accepts one connection and writes given chunks with a pause between them
+    fn drip_server(chunks: Vec<&'static [u8]>, pause: Duration) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            read_request(&mut stream);
+            for chunk in chunks {
+                if stream.write_all(chunk).is_err() {
+                    return;
+                }
+                thread::sleep(pause);
+            }
+            thread::sleep(Duration::from_secs(2));
+        });
+        port
+    }
+
+    fn local_request(port: u16, timeouts: Timeouts) -> Request {
+        let mut request =
+            Request::new(&format!("http://127.0.0.1:{}/", port), RequestTypes::GET).unwrap();
+        request.set_env_proxy(false);
+        request.set_timeouts(timeouts);
+        request
+    }
+
+    #[test]
+    fn read_idle_timeout() {
+        let port = drip_server(vec![], Duration::ZERO);
+        let mut request = local_request(
+            port,
+            Timeouts {
+                read: Some(Duration::from_millis(200)),
+                ..Timeouts::default()
+            },
+        );
+        assert!(matches!(
+            request.send(),
+            Err(RequestErrors::ConnectionTimeout(TimeoutPhase::Read))
+        ));
+    }
+
+    #[test]
+    fn connect_timeout() {
+        //Listener with a full backlog drops new handshakes, so connecting hangs
+        let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+        listener
+            .bind(&SocketAddr::from(([127, 0, 0, 1], 0)).into())
+            .unwrap();
+        listener.listen(0).unwrap();
+        let address = listener.local_addr().unwrap().as_socket().unwrap();
+        let mut backlog = Vec::new();
+        while let Ok(stream) = TcpStream::connect_timeout(&address, Duration::from_millis(200)) {
+            backlog.push(stream);
+            assert!(backlog.len() < 16, "backlog of the listener never filled");
+        }
+
+        let mut request = local_request(
+            address.port(),
+            Timeouts {
+                connect: Some(Duration::from_millis(200)),
+                ..Timeouts::default()
+            },
+        );
+        assert!(matches!(
+            request.send(),
+            Err(RequestErrors::ConnectionTimeout(TimeoutPhase::Connect))
+        ));
+    }
+
+    #[test]
+    fn write_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || {
+            //Peer never reads, so the body fills the socket buffers
+            let (_stream, _) = listener.accept().unwrap();
+            thread::sleep(Duration::from_secs(5));
+        });
+
+        let mut request = local_request(
+            port,
+            Timeouts {
+                write: Some(Duration::from_millis(200)),
+                ..Timeouts::default()
+            },
+        );
+        let body = vec![b'x'; 64 * 1024 * 1024];
+        assert!(matches!(
+            request.send_with_body(&mut body.as_slice()),
+            Err(RequestErrors::ConnectionTimeout(TimeoutPhase::Write))
+        ));
+    }
+
+    #[test]
+    fn deadline_while_reading_headers() {
+        let port = drip_server(vec![b"H"; 100], Duration::from_millis(50));
+        let mut request = local_request(
+            port,
+            Timeouts {
+                read: Some(Duration::from_secs(1)),
+                total: Some(Duration::from_millis(300)),
+                ..Timeouts::default()
+            },
+        );
+        let started = Instant::now();
+        assert!(matches!(
+            request.send(),
+            Err(RequestErrors::ConnectionTimeout(TimeoutPhase::Deadline))
+        ));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn deadline_while_reading_body() {
+        let mut chunks: Vec<&'static [u8]> = vec![b"HTTP/1.1 200 OK\r\n\r\n"];
+        chunks.extend(vec![b"body" as &[u8]; 100]);
+        let port = drip_server(chunks, Duration::from_millis(50));
+        let mut request = local_request(
+            port,
+            Timeouts {
+                total: Some(Duration::from_millis(400)),
+                ..Timeouts::default()
+            },
+        );
+        let mut response = request.send().unwrap();
+        let mut body = Vec::new();
+        let error = response.stream.read_to_end(&mut body).unwrap_err();
+        assert!(matches!(
+            RequestErrors::from(error),
+            RequestErrors::ConnectionTimeout(TimeoutPhase::Deadline)
+        ));
+    }
+
+    #[test]
+    fn races_address_families() {
+        let port = drip_server(vec![b"HTTP/1.1 204 No Content\r\n\r\n"], Duration::ZERO);
+        let mut request = local_request(port, Timeouts::default());
+        //Server only listens on 127.0.0.1, the other loopback addresses refuse the connection
+        request.set_resolver(Arc::new(FixedResolver(vec![
+            "::1".parse().unwrap(),
+            "127.0.0.2".parse().unwrap(),
+            "::1".parse().unwrap(),
+            "127.0.0.1".parse().unwrap(),
+        ])));
+        let started = Instant::now();
+        let response = request.send().unwrap();
+        assert_eq!(response.response_info.status_code, 204);
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn forced_address_family() {
+        let port = drip_server(vec![b"HTTP/1.1 204 No Content\r\n\r\n"], Duration::ZERO);
+        let mut request = local_request(port, Timeouts::default());
+        request.set_resolver(Arc::new(FixedResolver(vec![
+            "100::1".parse().unwrap(),
+            "127.0.0.1".parse().unwrap(),
+        ])));
+        request.set_address_family(AddressFamily::Ipv4Only);
+        assert!(request.send().is_ok());
+
+        let mut request = local_request(port, Timeouts::default());
+        request.set_resolver(Arc::new(FixedResolver(vec!["127.0.0.1".parse().unwrap()])));
+        request.set_address_family(AddressFamily::Ipv6Only);
+        assert!(matches!(
+            request.send(),
+            Err(RequestErrors::ConnectionError(e)) if e == "No IPv6 address available for 127.0.0.1"
+        ));
+    }
+
+    #[test]
+    fn binds_local_address() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let (mut stream, peer) = listener.accept().unwrap();
+            read_request(&mut stream);
+            stream
+                .write_all(b"HTTP/1.1 204 No Content\r\n\r\n")
+                .unwrap();
+            sender.send(peer).unwrap();
+        });
+
+        let mut request = local_request(port, Timeouts::default());
+        request.set_socket_options(SocketOptions {
+            local_address: Some("127.0.0.1".parse().unwrap()),
+            local_port_range: Some(47000..=47100),
+            nodelay: Some(true),
+            keepalive: Some(Duration::from_secs(30)),
+            send_buffer_size: Some(64 * 1024),
+            recv_buffer_size: Some(64 * 1024),
+        });
+        assert!(request.send().is_ok());
+        let peer = receiver.recv().unwrap();
+        assert_eq!(peer.ip(), "127.0.0.1".parse::<IpAddr>().unwrap());
+        assert!((47000..=47100).contains(&peer.port()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_socket_url() {
+        use std::os::unix::net::UnixListener;
+
+        let path = std::env::temp_dir().join(format!("menemen-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let head = read_head(&mut stream);
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n[]")
+                .unwrap();
+            head
+        });
+
+        let url = format!("unix://{}:/v1.43/containers/json?all=1", path.display());
+        let mut request = Request::new(&url, RequestTypes::GET).unwrap();
+        let mut response = request.send().unwrap();
+        let mut body = String::new();
+        response.stream.read_to_string(&mut body).unwrap();
+        assert_eq!(body, "[]");
+
+        let head = server.join().unwrap();
+        assert!(head.starts_with("GET /v1.43/containers/json?all=1 HTTP/1.1\r\n"));
+        assert!(head.contains("Host:localhost\r\n"));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -9,7 +9,7 @@ mod tests {
             "http://example.com/path/to/resource?foo=bar&baz=qux".to_string(),
         )
         .unwrap();
-        assert_eq!(url.is_https, false);
+        assert!(!url.is_https);
         assert_eq!(url.host, "example.com".to_string());
         assert_eq!(url.port, 80);
         assert_eq!(
@@ -35,7 +35,7 @@ mod tests {
             "https://example.com/test?q1=123&q2=456".to_string(),
         )
         .unwrap();
-        assert_eq!(url.is_https, true);
+        assert!(url.is_https);
         assert_eq!(url.host, "example.com".to_string());
         assert_eq!(url.port, 443);
         assert_eq!(url.paths, vec!["test".to_string()]);
@@ -58,7 +58,7 @@ mod tests {
             "http://example.com:8080/foo/bar?baz=qux".to_string(),
         )
         .unwrap();
-        assert_eq!(url.is_https, false);
+        assert!(!url.is_https);
         assert_eq!(url.host, "example.com".to_string());
         assert_eq!(url.port, 8080);
         assert_eq!(url.paths, vec!["foo".to_string(), "bar".to_string()]);
@@ -75,7 +75,7 @@ mod tests {
             "https://example.com/path/to/my/resource?param1=value1&param2=value2".to_string(),
         )
         .unwrap();
-        assert_eq!(url.is_https, true);
+        assert!(url.is_https);
         assert_eq!(url.host, "example.com".to_string());
         assert_eq!(url.port, 443);
         assert_eq!(
@@ -103,10 +103,27 @@ mod tests {
 
         // Test URLs with no path and no query parameters
         let url = menemen::url::Url::build_from_string("https://example.com".to_string()).unwrap();
-        assert_eq!(url.is_https, true);
+        assert!(url.is_https);
         assert_eq!(url.host, "example.com".to_string());
         assert_eq!(url.port, 443);
         assert_eq!(url.paths.len(), 0);
         assert_eq!(url.query_params, vec![]);
     }
+
+    #[test]
+    fn query_sent_as_written() {
+        let mut url = menemen::url::Url::build_from_string(
+            "http://example.com/search?b=%20c~d&e=f+g".to_string(),
+        )
+        .unwrap();
+        assert_eq!(url.query_params[0].value, " c~d");
+        assert_eq!(url.join_query_params(), "b=%20c~d&e=f+g");
+
+        url.query_params[1].value = "h i".to_string();
+        url.query_params.push(QueryParam {
+            name: "j&k".to_string(),
+            value: "~".to_string(),
+        });
+        assert_eq!(url.join_query_params(), "b=%20c~d&e=h+i&j%26k=%7E");
+    }
 }